@@ -1,70 +1,52 @@
+use crate::commands::{collisions::check_alias_name, Context};
 use crate::file_management::database::aliases::add_alias as db_add_alias;
-use crate::file_management::update_runcom;
 use crate::{
     error,
-    file_management::{
-        database::{aliases::get_alias_by_name, setupdb},
-        Alias,
-    },
+    file_management::{database::aliases::get_alias_by_name, Alias},
+    helpers::editor::read_stdin_or_edit,
     success,
 };
 use console::style;
 
-use super::validate_alias;
+use super::{check_dependency_cycle, validate_alias};
+
+/// Read the command for alias `name` from stdin or `$EDITOR`, returned as `name=command`
+pub fn read_alias_command(ctx: &Context, name: &str) -> Option<String> {
+    let command = match read_stdin_or_edit(&format!("{}.sh", name)) {
+        Ok(command) => command,
+        Err(err) => {
+            error!(err);
+            return None;
+        }
+    };
+
+    // Editors end files with a newline that isn't part of the command
+    let command = command.trim_end_matches(['\n', '\r']);
+    if command.trim().is_empty() {
+        error!("No command given");
+        return None;
+    }
+    Some(format!("{}={}", name, ctx.shell.quote(command)))
+}
 
 pub fn add_alias(
-    runcom_file: &str,
-    db_file: &str,
+    ctx: &Context,
     command: &str,
     description: &str,
     group_id: i32,
+    after: Option<&str>,
+    strict: bool,
 ) {
-    // Split command by = (name=value)
-    let alias_command = command.trim_start_matches("alias ");
-    let split_command: Vec<&str> = alias_command.split('=').collect();
-    let name = split_command[0];
-    let value = split_command.last().copied().unwrap();
-
-    // surround value with quotes if not already existing
-    let command = if value.starts_with('"') && value.ends_with('"')
-        || value.starts_with('\'') && value.ends_with('\'')
-    {
-        format!("{}={}", name, value)
-    } else {
-        format!("{}=\"{}\"", name, value)
-    };
-
-    let alias_command = match validate_alias(&command) {
-        true => command.to_string(),
-        false => {
-            error!("Command must be in format alias_name=\"command\"");
-            return;
-        }
-    };
-
-    let conn = match setupdb(db_file) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to alias database");
-            return;
-        }
-    };
-
-    let _ = match get_alias_by_name(&conn, name) {
-        Ok(_) => {
-            eprintln!("NAME: {}", name);
-            error!("Alias already exists");
+    let (name, alias_command) = match validate_alias(ctx.shell, command) {
+        Ok(alias) => alias,
+        Err(err) => {
+            error!(err);
             return;
         }
-        Err(_) => false,
     };
 
-    let mut alias_command = alias_command.split('=').collect::<Vec<&str>>()[1].to_string();
-    if alias_command.starts_with('"') {
-        alias_command.remove(0);
-        if alias_command.ends_with('"') {
-            alias_command.pop();
-        }
+    if !check_alias_name(ctx, name, strict) {
+        return;
     }
 
     let alias = Alias {
@@ -73,9 +55,22 @@ pub fn add_alias(
         description: description.to_string(),
         enabled: true,
         group_id,
+        after: after.map(|a| a.to_string()),
     };
 
-    match db_add_alias(&conn, &alias) {
+    if let Some(after) = after {
+        if get_alias_by_name(&ctx.conn, after).is_err() {
+            error!(format!("Could not find alias {}", style(after).bold()));
+            return;
+        }
+    }
+
+    if let Err(err) = check_dependency_cycle(&ctx.conn, &alias) {
+        error!(err);
+        return;
+    }
+
+    match ctx.commit(|tx| db_add_alias(tx, &alias).map_err(String::from)) {
         Ok(()) => {
             success!("Alias created successfully");
             println!(
@@ -83,12 +78,10 @@ pub fn add_alias(
                 style("`exec \"$SHELL\"`").bold().italic()
             );
         }
-        Err(_) => {
-            error!("issue creating alias");
+        Err(err) => {
+            error!(format!("issue creating alias: {}", err));
         }
     }
-
-    update_runcom(runcom_file, db_file);
 }
 
 #[cfg(test)]
@@ -102,17 +95,18 @@ mod tests {
         let db_path = "test_add_alias_command.db";
         let rc_path = "test_add_alias_command_rc";
         let command1 = r#"alias test1="echo "test command 1"""#;
-        let conn = setupdb(db_path).unwrap();
+        let ctx = Context::new(rc_path, db_path).unwrap();
 
-        add_alias(rc_path, db_path, command1, "", 1);
+        add_alias(&ctx, command1, "", 1, None, false);
 
-        let aliases = get_all_aliases(&conn);
+        let aliases = get_all_aliases(&ctx.conn);
         let alias_truth = vec![Alias {
             name: "test1".to_string(),
             command: r#"echo "test command 1""#.to_string(),
             description: "".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         }];
         assert_eq!(aliases, alias_truth);
 
@@ -126,10 +120,37 @@ mod tests {
                 enabled: true,
                 // TODO: This will need to be changed when runcom get_all_aliases can detect group_id
                 group_id: 0,
+                after: None,
             }]
         );
 
         std::fs::remove_file(db_path).expect("Error cleaning up test files");
         std::fs::remove_file(rc_path).expect("Error cleaning up test files");
     }
+
+    #[test]
+    fn add_alias_cycle_test() {
+        let db_path = "add_alias_cycle_test.db";
+        let rc_path = "add_alias_cycle_test_rc";
+        let ctx = Context::new(rc_path, db_path).unwrap();
+
+        add_alias(&ctx, r#"gl="git log""#, "", 1, None, false);
+        add_alias(&ctx, r#"gll="gl --oneline""#, "", 1, None, false);
+        add_alias(&ctx, r#"ls="ls --color""#, "", 1, None, false);
+        add_alias(&ctx, r#"glx="echo x""#, "", 1, Some("gll"), false);
+        // y calls x which calls y
+        add_alias(&ctx, r#"x="y""#, "", 1, None, false);
+        add_alias(&ctx, r#"y="x""#, "", 1, None, false);
+        add_alias(&ctx, r#"gs="git status""#, "", 1, Some("missing"), false);
+
+        let names: Vec<String> = get_all_aliases(&ctx.conn)
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(names, vec!["gl", "gll", "ls", "glx", "x"]);
+
+        std::fs::remove_file(db_path).expect("Error cleaning up test files");
+        std::fs::remove_file(rc_path).expect("Error cleaning up test files");
+        std::fs::remove_file(format!("{}.bak", rc_path)).expect("Error cleaning up test files");
+    }
 }
@@ -1,21 +1,18 @@
 use crate::{
     commands::{
-        aliases::{confirm_alias, fuzzy_get_alias},
+        aliases::{check_dependency_cycle, confirm_alias, fuzzy_get_alias, validate_alias_name},
+        collisions::check_alias_name,
         groups::fuzzy_get_group,
+        Context,
     },
     error,
-    file_management::{
-        database::{aliases::update_alias, db_conn},
-        update_runcom, Alias,
-    },
+    file_management::{database::aliases::update_alias, Alias},
     success,
 };
 use console::style;
 
-// TODO: abstract out connection and fuzzy get alias to private function
-
-fn get_alias(db_file: &str, alias_name: &str) -> Result<Alias, &'static str> {
-    let alias = match fuzzy_get_alias(alias_name, db_file) {
+fn get_alias(ctx: &Context, alias_name: &str) -> Result<Alias, &'static str> {
+    let alias = match fuzzy_get_alias(&ctx.conn, alias_name) {
         Some(alias) => alias,
         None => return Err("Could not file alias"),
     };
@@ -26,23 +23,14 @@ fn get_alias(db_file: &str, alias_name: &str) -> Result<Alias, &'static str> {
     Ok(alias)
 }
 
-fn edit_alias(
-    runcom_file: &str,
-    db_file: &str,
-    old_alias: &str,
-    new_alias: &Alias,
-    success_msg: String,
-) {
-    let conn = &db_conn(db_file);
-    let _ = match update_alias(conn, old_alias, new_alias.clone()) {
-        Ok(_) => true,
-        Err(_) => {
-            error!("Could not rename alias");
-            return;
-        }
-    };
+fn edit_alias(ctx: &Context, old_alias: &str, new_alias: &Alias, success_msg: String) {
+    if let Err(err) =
+        ctx.commit(|tx| update_alias(tx, old_alias, new_alias.clone()).map_err(String::from))
+    {
+        error!(format!("Could not update alias: {}", err));
+        return;
+    }
 
-    update_runcom(runcom_file, db_file);
     success!(success_msg);
     println!(
         "Please run {} to activate changes",
@@ -50,44 +38,59 @@ fn edit_alias(
     );
 }
 
-pub fn rename(runcom_file: &str, db_file: &str, old_name: &str, new_name: &str) {
-    let mut alias = match get_alias(db_file, old_name) {
+pub fn rename(ctx: &Context, old_name: &str, new_name: &str, strict: bool) {
+    let mut alias = match get_alias(ctx, old_name) {
         Ok(alias) => alias,
         Err(e) => {
-            error!(format!("{}", e));
+            error!(e.to_string());
             return;
         }
     };
 
+    if let Err(err) = validate_alias_name(ctx.shell, new_name) {
+        error!(err);
+        return;
+    }
+    if !check_alias_name(ctx, new_name, strict) {
+        return;
+    }
+
     let old_name = alias.name;
     alias.name = new_name.to_string();
 
-    edit_alias(
-        runcom_file,
-        db_file,
-        &old_name,
-        &alias,
-        format!(
-            "Alias {} has been renamed to {}",
-            style(old_name.clone()).bold().italic(),
-            style(new_name).italic().bold()
-        ),
+    // Aliases that use the new name now depend on this one, so check for cycles after renaming
+    let renamed = ctx.commit(|tx| {
+        update_alias(tx, &old_name, alias.clone())?;
+        check_dependency_cycle(tx, &alias)
+    });
+    if let Err(err) = renamed {
+        error!(format!("Could not update alias: {}", err));
+        return;
+    }
+
+    success!(format!(
+        "Alias {} has been renamed to {}",
+        style(old_name.clone()).bold().italic(),
+        style(new_name).italic().bold()
+    ));
+    println!(
+        "Please run {} to activate changes",
+        style("`exec \"$SHELL\"`").bold().italic()
     );
 }
 
-pub fn toggle_alias(runcom_file: &str, db_file: &str, alias_name: &str) {
-    let mut alias = match get_alias(db_file, alias_name) {
+pub fn toggle_alias(ctx: &Context, alias_name: &str) {
+    let mut alias = match get_alias(ctx, alias_name) {
         Ok(alias) => alias,
         Err(e) => {
-            error!(format!("{}", e));
+            error!(e.to_string());
             return;
         }
     };
     alias.enabled = !alias.enabled;
 
     edit_alias(
-        runcom_file,
-        db_file,
+        ctx,
         &alias.name,
         &alias,
         format!(
@@ -102,16 +105,21 @@ pub fn toggle_alias(runcom_file: &str, db_file: &str, alias_name: &str) {
     );
 }
 
-pub fn move_alias_group(runcom_file: &str, db_file: &str, alias_name: &str, group_name: &str, _new_group: Option<&str>) {
-    let mut alias = match get_alias(db_file, alias_name) {
+pub fn move_alias_group(
+    ctx: &Context,
+    alias_name: &str,
+    group_name: &str,
+    _new_group: Option<&str>,
+) {
+    let mut alias = match get_alias(ctx, alias_name) {
         Ok(alias) => alias,
         Err(e) => {
-            error!(format!("{}", e));
+            error!(e.to_string());
             return;
         }
     };
 
-    let group = match fuzzy_get_group(db_file, group_name) {
+    let group = match fuzzy_get_group(&ctx.conn, group_name) {
         Some(group) => group,
         None => {
             error!("Group not found");
@@ -128,8 +136,7 @@ pub fn move_alias_group(runcom_file: &str, db_file: &str, alias_name: &str, grou
     alias.group_id = group.id;
 
     edit_alias(
-        runcom_file,
-        db_file,
+        ctx,
         &alias.name,
         &alias,
         format!(
@@ -147,7 +154,6 @@ mod tests {
 
     use crate::file_management::database::aliases::get_all_aliases;
     use crate::file_management::database::groups::create_group;
-    use crate::file_management::database::setupdb;
     use crate::file_management::runcom::read_aliases;
     use crate::file_management::Alias;
 
@@ -156,17 +162,18 @@ mod tests {
         let db_path = "toggle_alias.db";
         let rc_path = "toggle_alias_rc";
         let command1 = r#"alias test1="echo "test command 1"""#;
-        let conn = setupdb(db_path).unwrap();
+        let ctx = Context::new(rc_path, db_path).unwrap();
 
-        add_alias(rc_path, db_path, command1, "", 1);
+        add_alias(&ctx, command1, "", 1, None, false);
 
-        let aliases = get_all_aliases(&conn);
+        let aliases = get_all_aliases(&ctx.conn);
         let alias_truth = vec![Alias {
             name: "test1".to_string(),
             command: r#"echo "test command 1""#.to_string(),
             description: "".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         }];
         assert_eq!(aliases, alias_truth);
 
@@ -180,10 +187,11 @@ mod tests {
                 enabled: true,
                 // TODO: This will need to be changed when runcom get_all_aliases can detect group_id
                 group_id: 0,
+                after: None,
             }]
         );
 
-        toggle_alias(rc_path, db_path, "test1");
+        toggle_alias(&ctx, "test1");
 
         let truth = vec![Alias {
             name: "test1".to_string(),
@@ -191,14 +199,16 @@ mod tests {
             description: "".to_string(),
             enabled: false,
             group_id: 1,
+            after: None,
         }];
 
-        assert_eq!(get_all_aliases(&conn), truth);
+        assert_eq!(get_all_aliases(&ctx.conn), truth);
 
         assert_eq!(read_aliases(rc_path).unwrap(), Vec::new());
 
         std::fs::remove_file(db_path).expect("Error cleaning up test files");
         std::fs::remove_file(rc_path).expect("Error cleaning up test files");
+        std::fs::remove_file(format!("{}.bak", rc_path)).expect("Error cleaning up test files");
     }
 
     #[test]
@@ -206,17 +216,18 @@ mod tests {
         let db_path = "rename_alias.db";
         let rc_path = "rename_alias_rc";
         let command1 = r#"alias test1="echo "test command 1"""#;
-        let conn = setupdb(db_path).unwrap();
+        let ctx = Context::new(rc_path, db_path).unwrap();
 
-        add_alias(rc_path, db_path, command1, "", 1);
+        add_alias(&ctx, command1, "", 1, None, false);
 
-        let aliases = get_all_aliases(&conn);
+        let aliases = get_all_aliases(&ctx.conn);
         let alias_truth = vec![Alias {
             name: "test1".to_string(),
             command: r#"echo "test command 1""#.to_string(),
             description: "".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         }];
         assert_eq!(aliases, alias_truth);
 
@@ -230,10 +241,11 @@ mod tests {
                 enabled: true,
                 // TODO: This will need to be changed when runcom get_all_aliases can detect group_id
                 group_id: 0,
+                after: None,
             }]
         );
 
-        rename(rc_path, db_path, "test1", "test2");
+        rename(&ctx, "test1", "test2", false);
 
         let truth = vec![Alias {
             name: "test2".to_string(),
@@ -241,12 +253,24 @@ mod tests {
             description: "".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         }];
 
-        assert_eq!(get_all_aliases(&conn), truth);
+        assert_eq!(get_all_aliases(&ctx.conn), truth);
+
+        // Renaming to a name another alias uses must not create a cycle
+        add_alias(&ctx, r#"alias uses_b="b --flag""#, "", 1, None, false);
+        add_alias(&ctx, r#"alias uses_a="uses_b""#, "", 1, None, false);
+        rename(&ctx, "uses_a", "b", false);
+        let names: Vec<String> = get_all_aliases(&ctx.conn)
+            .into_iter()
+            .map(|alias| alias.name)
+            .collect();
+        assert_eq!(names, vec!["test2", "uses_b", "uses_a"]);
 
         std::fs::remove_file(db_path).expect("Error cleaning up test files");
         std::fs::remove_file(rc_path).expect("Error cleaning up test files");
+        std::fs::remove_file(format!("{}.bak", rc_path)).expect("Error cleaning up test files");
     }
 
     #[test]
@@ -254,17 +278,18 @@ mod tests {
         let db_path = "move_group_alias.db";
         let rc_path = "move_group_alias_rc";
         let command1 = r#"alias test1="echo "test command 1"""#;
-        let conn = setupdb(db_path).unwrap();
+        let ctx = Context::new(rc_path, db_path).unwrap();
 
-        add_alias(rc_path, db_path, command1, "", 1);
+        add_alias(&ctx, command1, "", 1, None, false);
 
-        let aliases = get_all_aliases(&conn);
+        let aliases = get_all_aliases(&ctx.conn);
         let alias_truth = vec![Alias {
             name: "test1".to_string(),
             command: r#"echo "test command 1""#.to_string(),
             description: "".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         }];
         assert_eq!(aliases, alias_truth);
 
@@ -278,11 +303,12 @@ mod tests {
                 enabled: true,
                 // TODO: This will need to be changed when runcom get_all_aliases can detect group_id
                 group_id: 0,
+                after: None,
             }]
         );
 
-        create_group(&conn, "test_group_1");
-        move_alias_group(rc_path, db_path, "test1", "test_group_1", None);
+        create_group(&ctx.conn, "test_group_1");
+        move_alias_group(&ctx, "test1", "test_group_1", None);
 
         let truth = vec![Alias {
             name: "test1".to_string(),
@@ -290,11 +316,13 @@ mod tests {
             description: "".to_string(),
             enabled: true,
             group_id: 2,
+            after: None,
         }];
 
-        assert_eq!(get_all_aliases(&conn), truth);
+        assert_eq!(get_all_aliases(&ctx.conn), truth);
 
         std::fs::remove_file(db_path).expect("Error cleaning up test files");
         std::fs::remove_file(rc_path).expect("Error cleaning up test files");
+        std::fs::remove_file(format!("{}.bak", rc_path)).expect("Error cleaning up test files");
     }
 }
@@ -1,22 +1,36 @@
 use crate::{
-    error,
-    file_management::database::{aliases::get_all_aliases, setupdb},
-    warning,
+    commands::Context, error, file_management::database::aliases::get_all_aliases, warning,
 };
 use console::style;
 
 use super::fuzzy_get_alias;
 
-pub fn list_aliases(db_file: &str, disabled: bool) {
-    let conn = match setupdb(db_file) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to database");
-            return;
-        }
-    };
+/// Longest preview shown for a multi-line command
+const PREVIEW_LENGTH: usize = 60;
+
+/// One line version of a command for listings, the lines of a multi-line command are joined
+/// with ⏎ and cut short if needed
+pub fn command_preview(command: &str) -> String {
+    if !command.contains('\n') {
+        return command.to_string();
+    }
+
+    let preview = command
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" ⏎ ");
+    if preview.chars().count() <= PREVIEW_LENGTH {
+        return preview;
+    }
+    let mut preview: String = preview.chars().take(PREVIEW_LENGTH - 1).collect();
+    preview.push('…');
+    preview
+}
 
-    let aliases = get_all_aliases(&conn);
+pub fn list_aliases(ctx: &Context, disabled: bool) {
+    let aliases = get_all_aliases(&ctx.conn);
 
     if aliases.is_empty() {
         warning!("No aliases found");
@@ -28,24 +42,24 @@ pub fn list_aliases(db_file: &str, disabled: bool) {
             println!(
                 "✅ {}-> {}",
                 style(alias.name).green(),
-                style(alias.command).green()
+                style(command_preview(&alias.command)).green()
             );
         } else if !alias.enabled {
             println!(
                 "❌ {} -> {} ",
                 style(alias.name).red(),
-                style(alias.command).red()
+                style(command_preview(&alias.command)).red()
             );
         }
     }
 }
 
-pub fn alias_manual(db_file: &str, name: &str) {
+pub fn alias_manual(ctx: &Context, name: &str) {
     // Print manual for alias
     // Fuzzy get alias, if name not same lsit similar aliases
     // If no similar aliases, print error
 
-    let alias = fuzzy_get_alias(name, db_file);
+    let alias = fuzzy_get_alias(&ctx.conn, name);
     match alias {
         Some(alias) => {
             if alias.name != name {
@@ -66,3 +80,23 @@ pub fn alias_manual(db_file: &str, name: &str) {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn command_preview_test() {
+        assert_eq!(command_preview("git status"), "git status");
+        assert_eq!(
+            command_preview("cd ~/src\n\n  make \"$@\"\n"),
+            "cd ~/src ⏎ make \"$@\""
+        );
+
+        let long = "echo one\n".repeat(10);
+        let preview = command_preview(&long);
+        assert_eq!(preview.chars().count(), PREVIEW_LENGTH);
+        assert!(preview.starts_with("echo one ⏎ echo one"));
+        assert!(preview.ends_with('…'));
+    }
+}
@@ -5,45 +5,108 @@ pub mod remove;
 pub mod update;
 
 use crate::{
-    file_management::{
-        database::{aliases::get_all_aliases, setupdb},
-        Alias,
-    },
-    helpers::messages::error,
+    file_management::{alias_order::find_cycle, database::aliases::get_all_aliases, Alias},
+    helpers::shell::Shell,
 };
-use fancy_regex::Regex;
+use console::style;
+use rusqlite::Connection;
 
 fn confirm_alias(alias: &Alias) -> bool {
     // Ask for confirmation
     crate::helpers::questions::yesno!(format!("Did you mean {}?", alias.name)).unwrap()
 }
 
-pub fn validate_alias(alias: &str) -> bool {
-    // TODO: Add warning when not using single quotes
-    // single quotes prevent commands within the alias to be executed
-    let pattern = r#"(?:alias\s+)?(\w+)=([\'"])((?:\\.|(?!\2).)*)\2"#;
-    let re = Regex::new(pattern).unwrap();
-    println!("{}", alias);
-
-    match re.is_match(alias) {
-        Ok(value) => value,
-        Err(_) => {
-            error!("Error validating alias");
-            false
+/// Characters bash and zsh don't accept in alias names: whitespace, metacharacters, quoting
+/// characters, `/`, `$`, `` ` `` and `=`
+const INVALID_NAME_CHARS: &[char] = &[
+    '|', '&', ';', '(', ')', '<', '>', '\'', '"', '\\', '/', '$', '`', '=',
+];
+
+/// Words fish won't accept as function names, fish aliases are functions
+const FISH_RESERVED_NAMES: &[&str] = &[
+    "[", "_", "and", "argparse", "begin", "break", "builtin", "case", "command", "continue",
+    "else", "end", "eval", "exec", "for", "function", "if", "not", "or", "read", "return", "set",
+    "status", "string", "switch", "test", "time", "while",
+];
+
+/// Check `name` can be used as an alias name in `shell`
+pub fn validate_alias_name(shell: Shell, name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Alias name can't be empty".to_string());
+    }
+    if let Some(c) = name
+        .chars()
+        .find(|c| c.is_whitespace() || INVALID_NAME_CHARS.contains(c))
+    {
+        return Err(format!("Alias names can't contain {:?}", c));
+    }
+    // Read as an option by the alias builtin
+    if name.starts_with('-') {
+        return Err("Alias names can't start with -".to_string());
+    }
+    if shell == Shell::Fish && FISH_RESERVED_NAMES.contains(&name) {
+        return Err(format!("{} is a reserved word in fish", name));
+    }
+    Ok(())
+}
+
+/// Parse `name=command` as typed by the user and check it is a usable alias
+pub fn validate_alias(shell: Shell, alias: &str) -> Result<(&str, String), String> {
+    let (name, command) = match split_alias(shell, alias) {
+        Some((name, command)) if !command.is_empty() => (name, command),
+        _ => return Err("Command must be in format alias_name=\"command\"".to_string()),
+    };
+    validate_alias_name(shell, name)?;
+    Ok((name, command))
+}
+
+/// Split `name=command` as typed by the user into the alias name and the raw command to store
+///
+/// A value written as a quoted shell word (`x='a b'`, `x="a=b"`) is unquoted, anything else is
+/// taken as it is.
+pub fn split_alias(shell: Shell, alias: &str) -> Option<(&str, String)> {
+    let (name, value) = alias.trim().trim_start_matches("alias ").split_once('=')?;
+    // `alias x= y` defines x as nothing
+    if value.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    if !value.starts_with(['"', '\'']) {
+        return Some((name, value.to_string()));
+    }
+    match shell.parse_word(value) {
+        Some(command) => Some((name, command)),
+        // Quotes that don't form a single word such as "echo "hi"" or nym 0.1's 'echo \'hi\'',
+        // keep what's between the outer quotes with escaped outer quotes unescaped
+        None if value.len() >= 2 && value.ends_with(&value[..1]) => {
+            let quote = &value[..1];
+            let inner = &value[1..value.len() - 1];
+            Some((name, inner.replace(&format!("\\{}", quote), quote)))
         }
+        None => None,
     }
 }
 
-pub fn fuzzy_get_alias(name: &str, db_path: &str) -> Option<Alias> {
+/// Reject an alias that can't be loaded in any order, `alias` replaces any existing alias with
+/// the same name
+pub fn check_dependency_cycle(conn: &Connection, alias: &Alias) -> Result<(), String> {
+    let existing = get_all_aliases(conn);
+    let mut aliases: Vec<&Alias> = existing.iter().filter(|a| a.name != alias.name).collect();
+    aliases.push(alias);
+
+    match find_cycle(&aliases, aliases.len() - 1) {
+        Some(cycle) => Err(format!(
+            "Alias {} would create a dependency cycle: {}",
+            style(&alias.name).bold(),
+            cycle.join(" -> ")
+        )),
+        None => Ok(()),
+    }
+}
+
+pub fn fuzzy_get_alias(conn: &Connection, name: &str) -> Option<Alias> {
     // A function to get an alias by name, but also get the closest match if the name doesn't exist
-    let conn = match setupdb(db_path) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to database");
-            return None;
-        }
-    };
-    let aliases = get_all_aliases(&conn);
+    let aliases = get_all_aliases(conn);
     let mut closest_match: Option<Alias> = None;
     let mut closest_distance = usize::MAX;
 
@@ -70,9 +133,16 @@ fn validate_alias_test() {
         r#"alias alias_name='echo "test"'"#,
         r#"alias_name='echo "test"'"#,
         r#"alias alias_name="echo \"nested 'test'\"""#,
-        r#"alias_name='echo \'nested "test"\'""#,
+        r#"alias_name='echo \'nested "test"\''"#,
         r#"alias alias_name="echo \\"test\\"""#,
         r#"alias_name="echo \\"test\\"""#,
+        r#"..="cd ..""#,
+        r#"git-st="git status""#,
+        r#"k.get="kubectl get""#,
+        r#"+x="chmod +x""#,
+        r#"alias 1up="cd ..""#,
+        "gs=git status",
+        r#"and="echo and""#,
     ];
 
     let invalid_alias_strings = vec![
@@ -83,13 +153,58 @@ fn validate_alias_test() {
         r#"alias alias name="echo 'test'"#,
         r#"alias echo 'test'"#,
         r#"alias test="echo 'test'"#,
+        // Ends with a stray double quote, so the single quoted value is never closed
+        r#"alias_name='echo \'nested "test"\'""#,
+        r#"a/b="echo""#,
+        r#"$x="echo""#,
+        r#"a|b="echo""#,
+        r#"-x="echo""#,
     ];
 
     for alias in valid_alias_strings {
-        assert!(validate_alias(alias));
+        assert!(validate_alias(Shell::Bash, alias).is_ok(), "{}", alias);
+        assert!(validate_alias(Shell::Zsh, alias).is_ok(), "{}", alias);
     }
 
     for alias in invalid_alias_strings {
-        assert!(!validate_alias(alias));
+        assert!(validate_alias(Shell::Bash, alias).is_err(), "{}", alias);
+    }
+
+    // Fine in bash but reserved in fish
+    for alias in [r#"and="echo and""#, r#"begin="echo""#, r#"string="echo""#] {
+        assert!(validate_alias(Shell::Bash, alias).is_ok(), "{}", alias);
+        assert!(validate_alias(Shell::Fish, alias).is_err(), "{}", alias);
     }
+    assert!(validate_alias(Shell::Fish, r#"..="cd ..""#).is_ok());
+}
+
+#[test]
+fn split_alias_test() {
+    let split = |alias| split_alias(Shell::Bash, alias).map(|(n, c)| (n.to_string(), c));
+    let expected = |name: &str, command: &str| Some((name.to_string(), command.to_string()));
+
+    assert_eq!(split(r#"x="a=b""#), expected("x", "a=b"));
+    assert_eq!(split("alias x=a=b"), expected("x", "a=b"));
+    assert_eq!(
+        split(r"x='echo $HOME `pwd` \'"),
+        expected("x", r"echo $HOME `pwd` \")
+    );
+    assert_eq!(split(r#"gs=git status"#), expected("gs", "git status"));
+    // Parsed the way the shell would
+    assert_eq!(
+        split(r#"x="echo "nested"_quotes""#),
+        expected("x", "echo nested_quotes")
+    );
+    // Not a single word, so only the outer quotes are removed
+    assert_eq!(
+        split(r#"x="echo "nested quotes"""#),
+        expected("x", r#"echo "nested quotes""#)
+    );
+    // Single quotes escaped the way nym 0.1 accepted them
+    assert_eq!(
+        split(r#"x='echo \'nested "test"\''"#),
+        expected("x", r#"echo 'nested "test"'"#)
+    );
+    assert_eq!(split("no_value"), None);
+    assert_eq!(split(r#"x="open"#), None);
 }
@@ -1,20 +1,11 @@
 use super::{confirm_alias, fuzzy_get_alias};
+use crate::commands::Context;
 use crate::file_management::database::aliases::remove_alias as remove_alias_from_database;
-use crate::file_management::database::setupdb;
-use crate::file_management::update_runcom;
 use crate::{error, success};
 use console::style;
 
-pub fn remove_alias(runcom_file: &str, db_file: &str, alias_name: &str, force: bool) {
-    let conn = match setupdb(db_file) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to database");
-            return;
-        }
-    };
-
-    let alias = match fuzzy_get_alias(alias_name, db_file) {
+pub fn remove_alias(ctx: &Context, alias_name: &str, force: bool) {
+    let alias = match fuzzy_get_alias(&ctx.conn, alias_name) {
         Some(alias) => alias,
         None => {
             error!("Alias not found");
@@ -37,15 +28,13 @@ pub fn remove_alias(runcom_file: &str, db_file: &str, alias_name: &str, force: b
         std::process::exit(1);
     }
 
-    let _ = match remove_alias_from_database(&conn, &alias.name) {
-        Ok(_) => true,
-        Err(_) => {
-            error!("Could not remove alias");
-            return;
-        }
-    };
+    if let Err(err) =
+        ctx.commit(|tx| remove_alias_from_database(tx, &alias.name).map_err(String::from))
+    {
+        error!(format!("Could not remove alias: {}", err));
+        return;
+    }
 
-    update_runcom(runcom_file, db_file);
     success!("Alias removed successfully");
     println!(
         "Please run {} to activate changes",
@@ -67,17 +56,18 @@ mod tests {
         let db_path = "remove_alias.db";
         let rc_path = "remove_alias_rc";
         let command1 = r#"alias test1="echo "test command 1"""#;
-        let conn = setupdb(db_path).unwrap();
+        let ctx = Context::new(rc_path, db_path).unwrap();
 
-        add_alias(rc_path, db_path, command1, "", 1);
+        add_alias(&ctx, command1, "", 1, None, false);
 
-        let aliases = get_all_aliases(&conn);
+        let aliases = get_all_aliases(&ctx.conn);
         let alias_truth = vec![Alias {
             name: "test1".to_string(),
             command: r#"echo "test command 1""#.to_string(),
             description: "".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         }];
         assert_eq!(aliases, alias_truth);
 
@@ -91,16 +81,18 @@ mod tests {
                 enabled: true,
                 // TODO: This will need to be changed when runcom get_all_aliases can detect group_id
                 group_id: 0,
+                after: None,
             }]
         );
 
-        remove_alias(rc_path, db_path, "test1", true);
+        remove_alias(&ctx, "test1", true);
 
-        assert_eq!(get_all_aliases(&conn), Vec::new());
+        assert_eq!(get_all_aliases(&ctx.conn), Vec::new());
 
         assert_eq!(read_aliases(rc_path).unwrap(), Vec::new());
 
         std::fs::remove_file(db_path).expect("Error cleaning up test files");
         std::fs::remove_file(rc_path).expect("Error cleaning up test files");
+        std::fs::remove_file(format!("{}.bak", rc_path)).expect("Error cleaning up test files");
     }
 }
@@ -1,4 +1,153 @@
-#[allow(dead_code, unused_variables)]
-fn update_alias(db_file: &str, alias_name: &str, update: Option<String>) {
-    todo!()
+use console::style;
+
+use crate::{
+    commands::Context,
+    error,
+    file_management::{
+        database::aliases::{get_alias_by_name, update_alias as db_update_alias},
+        Alias,
+    },
+    helpers::{diff::print_diff, editor::edit_text},
+    info, success,
+};
+
+use super::{check_dependency_cycle, validate_alias};
+
+/// Ask for a new command and description, pre-filled with the current ones
+fn prompt_update(alias: &Alias) -> Option<(String, String)> {
+    let command = if alias.command.contains('\n') {
+        match edit_text(&alias.command, &format!("{}.sh", alias.name)) {
+            Ok(command) => command.trim_end_matches(['\n', '\r']).to_string(),
+            Err(err) => {
+                error!(err);
+                return None;
+            }
+        }
+    } else {
+        inquire::Text::new("Command:")
+            .with_initial_value(&alias.command)
+            .prompt()
+            .ok()?
+    };
+
+    let description = inquire::Text::new("Description:")
+        .with_initial_value(&alias.description)
+        .prompt()
+        .unwrap_or(alias.description.clone());
+    Some((command, description))
+}
+
+/// Change the command and/or description of an alias, prompting for both if neither is given
+pub fn update_alias(
+    ctx: &Context,
+    name: &str,
+    new_command: Option<&str>,
+    new_description: Option<&str>,
+) {
+    let alias = match get_alias_by_name(&ctx.conn, name) {
+        Ok(alias) => alias,
+        Err(_) => {
+            error!(format!("Could not find alias {}", style(name).bold()));
+            return;
+        }
+    };
+
+    let (command, description) = if new_command.is_none() && new_description.is_none() {
+        match prompt_update(&alias) {
+            Some(update) => update,
+            None => return,
+        }
+    } else {
+        (
+            new_command.unwrap_or(&alias.command).to_string(),
+            new_description.unwrap_or(&alias.description).to_string(),
+        )
+    };
+
+    // Same checks as adding the alias
+    let definition = format!("{}={}", alias.name, ctx.shell.quote(&command));
+    let command = match validate_alias(ctx.shell, &definition) {
+        Ok((_, command)) => command,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    if command == alias.command && description == alias.description {
+        info!(format!("Alias {} is unchanged", style(&alias.name).bold()));
+        return;
+    }
+
+    let updated = Alias {
+        command,
+        description,
+        ..alias.clone()
+    };
+    if let Err(err) = check_dependency_cycle(&ctx.conn, &updated) {
+        error!(err);
+        return;
+    }
+
+    if updated.command != alias.command {
+        println!("{}", style("Command").bold());
+        print_diff(&alias.command, &updated.command);
+    }
+    if updated.description != alias.description {
+        println!("{}", style("Description").bold());
+        print_diff(&alias.description, &updated.description);
+    }
+
+    let result =
+        ctx.commit(|tx| db_update_alias(tx, &alias.name, updated.clone()).map_err(String::from));
+    match result {
+        Ok(()) => {
+            success!(format!("Alias {} updated", style(&alias.name).bold()));
+            println!(
+                "Please run {} to activate changes",
+                style("`exec \"$SHELL\"`").bold().italic()
+            );
+        }
+        Err(err) => error!(format!("Could not update alias: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{commands::aliases::add::add_alias, file_management::runcom::read_aliases};
+
+    use super::*;
+
+    #[test]
+    fn update_alias_test() {
+        let db_file = "update_alias_test.db";
+        let rc_file = "update_alias_test_rc";
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        add_alias(&ctx, r#"gl="git log""#, "log", 1, None, false);
+        add_alias(&ctx, r#"gll="gl --oneline""#, "", 1, None, false);
+
+        update_alias(&ctx, "gl", Some("git log --graph $1"), None);
+        let alias = get_alias_by_name(&ctx.conn, "gl").unwrap();
+        assert_eq!(alias.command, "git log --graph $1");
+        assert_eq!(alias.description, "log");
+        assert_eq!(read_aliases(rc_file).unwrap()[0].command, alias.command);
+
+        update_alias(&ctx, "gl", None, Some("pretty log"));
+        let alias = get_alias_by_name(&ctx.conn, "gl").unwrap();
+        assert_eq!(alias.command, "git log --graph $1");
+        assert_eq!(alias.description, "pretty log");
+
+        // Invalid commands and dependency cycles are rejected
+        update_alias(&ctx, "gl", Some(""), None);
+        update_alias(&ctx, "gl", Some("gll"), None);
+        assert_eq!(
+            get_alias_by_name(&ctx.conn, "gl").unwrap().command,
+            "git log --graph $1"
+        );
+
+        std::fs::remove_file(db_file).expect("Error cleaning up test files");
+        std::fs::remove_file(rc_file).expect("Error cleaning up test files");
+        std::fs::remove_file(format!("{}.bak", rc_file)).expect("Error cleaning up test files");
+    }
 }
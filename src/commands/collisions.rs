@@ -0,0 +1,295 @@
+use std::path::{Path, PathBuf};
+
+use console::style;
+
+use crate::{
+    commands::Context,
+    error,
+    file_management::database::{
+        aliases::get_alias_by_name, groups::get_group_by_name, scripts::get_script_by_name,
+    },
+    warning,
+};
+
+/// Builtin commands of bash and zsh
+const SHELL_BUILTINS: &[&str] = &[
+    ".",
+    ":",
+    "[",
+    "alias",
+    "autoload",
+    "bg",
+    "bind",
+    "bindkey",
+    "break",
+    "builtin",
+    "caller",
+    "cd",
+    "command",
+    "compdef",
+    "compgen",
+    "complete",
+    "compopt",
+    "continue",
+    "declare",
+    "dirs",
+    "disown",
+    "echo",
+    "emulate",
+    "enable",
+    "eval",
+    "exec",
+    "exit",
+    "export",
+    "false",
+    "fc",
+    "fg",
+    "functions",
+    "getopts",
+    "hash",
+    "help",
+    "history",
+    "integer",
+    "jobs",
+    "kill",
+    "let",
+    "local",
+    "logout",
+    "mapfile",
+    "noglob",
+    "popd",
+    "print",
+    "printf",
+    "pushd",
+    "pwd",
+    "read",
+    "readarray",
+    "readonly",
+    "rehash",
+    "return",
+    "set",
+    "setopt",
+    "shift",
+    "shopt",
+    "source",
+    "suspend",
+    "test",
+    "times",
+    "trap",
+    "true",
+    "type",
+    "typeset",
+    "ulimit",
+    "umask",
+    "unalias",
+    "unset",
+    "unsetopt",
+    "wait",
+    "whence",
+    "where",
+    "which",
+    "zle",
+    "zmodload",
+    "zstyle",
+];
+
+/// Reserved words of bash and zsh
+const SHELL_KEYWORDS: &[&str] = &[
+    "!", "[[", "]]", "{", "}", "case", "coproc", "do", "done", "elif", "else", "end", "esac", "fi",
+    "for", "foreach", "function", "if", "in", "repeat", "select", "then", "time", "until", "while",
+];
+
+/// Something a new alias or script name clashes with
+#[derive(Debug, PartialEq)]
+pub enum Collision {
+    Alias,
+    Script,
+    Group,
+    /// The first executable with the name on `$PATH`
+    Executable(PathBuf),
+    Builtin,
+    Keyword,
+}
+
+impl Collision {
+    fn describe(&self, name: &str) -> String {
+        let name = style(name).bold();
+        match self {
+            Collision::Alias => format!("{} is already the name of an alias", name),
+            Collision::Script => format!("{} is already the name of a script", name),
+            Collision::Group => format!("{} is already the name of a group", name),
+            Collision::Executable(path) => {
+                format!("{} shadows {} on $PATH", name, path.display())
+            }
+            Collision::Builtin => format!("{} shadows a shell builtin", name),
+            Collision::Keyword => format!("{} is a shell keyword", name),
+        }
+    }
+}
+
+/// First executable called `name` in the directories of `path_var`, skipping `skip_dir` and
+/// anything below it
+pub(crate) fn find_executable(name: &str, path_var: &str, skip_dir: &Path) -> Option<PathBuf> {
+    // Every path starts with an empty one
+    let skip = |dir: &Path| !skip_dir.as_os_str().is_empty() && dir.starts_with(skip_dir);
+    std::env::split_paths(path_var)
+        .filter(|dir| !skip(dir))
+        .map(|dir| dir.join(name))
+        .find(|candidate| is_executable(candidate))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    match std::fs::metadata(path) {
+        Ok(metadata) => metadata.is_file() && metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+/// Everything `name` collides with: nym items, executables on `$PATH` and shell builtins and
+/// keywords
+pub fn find_collisions(ctx: &Context, name: &str) -> Vec<Collision> {
+    let mut collisions = Vec::new();
+
+    if get_alias_by_name(&ctx.conn, name).is_ok() {
+        collisions.push(Collision::Alias);
+    }
+    if get_script_by_name(&ctx.conn, name).is_ok() {
+        collisions.push(Collision::Script);
+    }
+    if get_group_by_name(&ctx.conn, name).is_ok() {
+        collisions.push(Collision::Group);
+    }
+
+    // nym's own scripts are on $PATH too, those are covered above
+    let path_var = std::env::var("PATH").unwrap_or_default();
+    if let Some(path) = find_executable(name, &path_var, ctx.nym_dir()) {
+        collisions.push(Collision::Executable(path));
+    }
+
+    if SHELL_BUILTINS.contains(&name) {
+        collisions.push(Collision::Builtin);
+    }
+    if SHELL_KEYWORDS.contains(&name) {
+        collisions.push(Collision::Keyword);
+    }
+    collisions
+}
+
+/// Report collisions for a new name, returns false if the name can't be used
+///
+/// A collision with `same_kind` (an item of the kind being named) always blocks, everything else
+/// is a warning unless `strict` is set.
+fn check_name(ctx: &Context, name: &str, same_kind: Collision, strict: bool) -> bool {
+    let collisions = find_collisions(ctx, name);
+
+    if collisions.contains(&same_kind) {
+        error!(same_kind.describe(name));
+        return false;
+    }
+    if collisions.is_empty() {
+        return true;
+    }
+
+    for collision in &collisions {
+        warning!(collision.describe(name));
+    }
+    if strict {
+        error!(format!(
+            "Not using {} because of --strict",
+            style(name).bold()
+        ));
+        return false;
+    }
+    true
+}
+
+pub fn check_alias_name(ctx: &Context, name: &str, strict: bool) -> bool {
+    check_name(ctx, name, Collision::Alias, strict)
+}
+
+pub fn check_script_name(ctx: &Context, name: &str, strict: bool) -> bool {
+    check_name(ctx, name, Collision::Script, strict)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_management::{database::aliases::add_alias, Alias};
+
+    use super::*;
+
+    #[test]
+    fn find_executable_test() {
+        let test_dir = Path::new("find_executable_test");
+        let bin_dir = test_dir.join("bin");
+        let skip_dir = test_dir.join("skip");
+        std::fs::create_dir_all(&bin_dir).expect("Error creating test dirs");
+        std::fs::create_dir_all(&skip_dir).expect("Error creating test dirs");
+
+        std::fs::write(bin_dir.join("plain"), "").unwrap();
+        std::fs::write(skip_dir.join("tool"), "").unwrap();
+        std::fs::write(bin_dir.join("tool"), "").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let executable = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(bin_dir.join("tool"), executable.clone()).unwrap();
+            std::fs::set_permissions(skip_dir.join("tool"), executable).unwrap();
+        }
+
+        let path_var = std::env::join_paths([&skip_dir, &bin_dir])
+            .unwrap()
+            .into_string()
+            .unwrap();
+        assert_eq!(
+            find_executable("tool", &path_var, &skip_dir),
+            Some(bin_dir.join("tool"))
+        );
+        #[cfg(unix)]
+        assert_eq!(find_executable("plain", &path_var, &skip_dir), None);
+        assert_eq!(find_executable("missing", &path_var, &skip_dir), None);
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+
+    #[test]
+    fn check_name_test() {
+        let db_file = "check_name_test.db";
+        let rc_file = "check_name_test_rc";
+        let ctx = Context::new(rc_file, db_file).unwrap();
+        add_alias(
+            &ctx.conn,
+            &Alias {
+                name: "nym_check_name_test".to_string(),
+                command: "echo".to_string(),
+                description: "".to_string(),
+                enabled: true,
+                group_id: 1,
+                after: None,
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            find_collisions(&ctx, "uncategorized"),
+            vec![Collision::Group]
+        );
+        assert!(find_collisions(&ctx, "then").contains(&Collision::Keyword));
+        assert!(find_collisions(&ctx, "cd").contains(&Collision::Builtin));
+
+        // An alias of the same name always blocks, a script name only warns
+        assert!(!check_alias_name(&ctx, "nym_check_name_test", false));
+        assert!(check_script_name(&ctx, "nym_check_name_test", false));
+        assert!(!check_script_name(&ctx, "nym_check_name_test", true));
+        assert!(check_alias_name(&ctx, "nym_check_name_unused", true));
+
+        std::fs::remove_file(db_file).expect("Error cleaning up test files");
+    }
+}
@@ -0,0 +1,62 @@
+use console::style;
+
+use crate::{
+    commands::{
+        scripts::check::{check_script, review_issues, FixIssues},
+        Context,
+    },
+    error,
+    file_management::{
+        database::scripts::{get_all_scripts, update_script as update_script_in_database},
+        Script,
+    },
+    success, warning,
+};
+
+/// Check every script and report anything that would stop it from running, returns the number
+/// of problems found
+///
+/// Scripts that aren't executable or have no `#!` line are fixed with `fix`, and the issues
+/// stored for each script are brought up to date.
+pub fn doctor(ctx: &Context, fix: bool) -> usize {
+    let mut found = 0;
+
+    for script in get_all_scripts(&ctx.conn) {
+        let problems = check_script(&script);
+        for problem in &problems {
+            warning!(format!(
+                "Script {}: {}",
+                style(&script.name).bold(),
+                problem.describe()
+            ));
+            found += 1;
+        }
+        if !problems.is_empty() {
+            continue;
+        }
+
+        let fix = if fix {
+            FixIssues::Always
+        } else {
+            FixIssues::Never
+        };
+        let issues = review_issues(&script, std::path::Path::new(&script.path), fix);
+        found += issues.len();
+        if issues != script.issues {
+            let updated = Script {
+                issues,
+                ..script.clone()
+            };
+            if let Err(err) = ctx.commit(|tx| {
+                update_script_in_database(tx, &script.name, &updated).map_err(String::from)
+            }) {
+                error!(format!("Could not update script {}: {}", script.name, err));
+            }
+        }
+    }
+
+    if found == 0 {
+        success!("No problems found");
+    }
+    found
+}
@@ -0,0 +1,153 @@
+use std::path::Path;
+use std::process::{Command, ExitStatus};
+
+use console::style;
+
+use crate::{
+    commands::{
+        scripts::{check::find_issues, launcher::launcher_dir, runs::run_logged},
+        Context,
+    },
+    error,
+    file_management::{
+        database::{aliases::get_alias_by_name, scripts::get_script_by_name},
+        Script,
+    },
+};
+
+/// Exit code a shell would give for `status`, 128 plus the signal if it was killed by one
+pub fn exit_code(status: ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(1)
+}
+
+/// Command running `script` without a shell, through its launcher if it has one
+fn script_command(script: &Script) -> Command {
+    let path = Path::new(&script.path);
+    let launcher = launcher_dir(script).join(path.file_name().unwrap_or_default());
+    if script.entry_point.is_none() && launcher.is_file() {
+        return Command::new(launcher);
+    }
+
+    // A script that can't run on its own is handed to its interpreter
+    match script.type_.interpreter() {
+        Some(interpreter) if !find_issues(path).is_empty() => {
+            let mut command = Command::new(interpreter);
+            command.arg(path);
+            command
+        }
+        _ => Command::new(path),
+    }
+}
+
+/// Run the alias, function or script called `name` with `args` appended, returns the exit code
+/// to leave nym with
+///
+/// Aliases and functions run in the runcom's shell with the runcom loaded, so aliases they use
+/// are expanded even though the shell isn't interactive. stdin, stdout and stderr are passed
+/// through, for scripts whose runs are logged the output is also copied to the log.
+pub fn exec(ctx: &Context, name: &str, args: &[String]) -> i32 {
+    let mut command = if let Ok(alias) = get_alias_by_name(&ctx.conn, name) {
+        if !alias.enabled {
+            error!(format!("Alias {} is disabled", style(name).bold()));
+            return 1;
+        }
+        let mut command_line = alias.name;
+        for arg in args {
+            command_line.push(' ');
+            command_line.push_str(&ctx.shell.quote(arg));
+        }
+
+        let mut command = Command::new(ctx.shell.program());
+        command
+            .arg("-c")
+            .arg(ctx.shell.run_with_runcom(&ctx.rc_file, &command_line));
+        command
+    } else if let Ok(script) = get_script_by_name(&ctx.conn, name) {
+        if !script.enabled {
+            error!(format!("Script {} is disabled", style(name).bold()));
+            return 1;
+        }
+        let mut command = script_command(&script);
+        command.args(args);
+        if script.log_runs {
+            return run_logged(ctx, &script, command, args).unwrap_or_else(|err| {
+                error!(err);
+                126
+            });
+        }
+        command
+    } else {
+        error!(format!("No alias or script called {}", style(name).bold()));
+        return 127;
+    };
+
+    match command.status() {
+        Ok(status) => exit_code(status),
+        Err(err) => {
+            error!(format!("Could not run {}: {}", style(name).bold(), err));
+            126
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::scripts::{add::add_script, check::FixIssues},
+        file_management::{database::aliases::add_alias, Alias},
+        helpers::shell::Shell,
+    };
+
+    use super::*;
+
+    #[test]
+    fn exec_test() {
+        let test_dir = "exec_test";
+        let db_file = "exec_test/nym.db";
+        let rc_file = "exec_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let mut ctx = Context::new(rc_file, db_file).unwrap();
+        ctx.shell = Shell::Bash;
+
+        let alias = |name: &str, command: &str| Alias {
+            name: name.to_string(),
+            command: command.to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id: 1,
+            after: None,
+        };
+        ctx.commit(|tx| {
+            add_alias(tx, &alias("exec_exit", "sh -c 'exit \"$1\"' sh"))?;
+            // Aliases used by other aliases are expanded
+            add_alias(tx, &alias("exec_nested", "exec_exit"))?;
+            add_alias(
+                tx,
+                &alias("exec_function", "shift\nexec_exit $(( $1 + 1 ))"),
+            )?;
+            Ok(())
+        })
+        .unwrap();
+
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(exec(&ctx, "exec_exit", &args(&["3"])), 3);
+        assert_eq!(exec(&ctx, "exec_nested", &args(&["4"])), 4);
+        assert_eq!(exec(&ctx, "exec_function", &args(&["x", "5"])), 6);
+        assert_eq!(exec(&ctx, "exec_missing", &[]), 127);
+
+        // Scripts run directly, given to their interpreter if they aren't executable
+        let source = "exec_test/exec_script.sh";
+        std::fs::write(source, "exit \"$#\"\n").unwrap();
+        add_script(&ctx, source, "", None, false, false, FixIssues::Never);
+        assert_eq!(exec(&ctx, "exec_script", &args(&["a b", "c"])), 2);
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
@@ -0,0 +1,272 @@
+use console::style;
+use rusqlite::Connection;
+
+use crate::{
+    commands::{aliases::list::command_preview, Context},
+    file_management::{
+        bin_dir::bin_entry_name,
+        database::{aliases::get_alias_by_name, scripts::get_all_scripts},
+    },
+    info, warning,
+};
+
+/// One step taken while expanding a command line
+#[derive(Debug, PartialEq)]
+pub enum Step {
+    /// The first word was an alias and got replaced by its command
+    Alias { name: String, command: String },
+    /// The first word runs a multi-line alias, which is written as a function
+    Function { name: String, body: String },
+    /// The first word runs a nym script
+    Script { name: String, path: String },
+}
+
+/// Why expansion stopped
+#[derive(Debug, PartialEq)]
+pub enum Stop {
+    /// The first word isn't an enabled nym alias or script
+    Command,
+    /// The first word is a function, its body isn't substituted into the command line
+    Function,
+    /// The first word is a nym script
+    Script,
+    /// The alias's command starts with its own name, the shell runs the real command
+    SelfReference(String),
+    /// An alias expanded back to an alias that was already expanded
+    Loop(Vec<String>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Expansion {
+    pub command: String,
+    pub steps: Vec<Step>,
+    pub stop: Stop,
+}
+
+/// Split off the first word of a command line, the rest keeps its leading whitespace
+fn split_first_word(command_line: &str) -> (&str, &str) {
+    let command_line = command_line.trim_start();
+    let end = command_line
+        .find(char::is_whitespace)
+        .unwrap_or(command_line.len());
+    command_line.split_at(end)
+}
+
+/// Resolve the first word of `command_line` through nym the way the shell would: aliases are
+/// replaced by their command until the first word is no longer an alias, and a function
+/// (multi-line alias) or script ends it.
+///
+/// Disabled aliases and scripts aren't in the runcom so they are skipped.
+pub fn expand_command(conn: &Connection, command_line: &str) -> Expansion {
+    let mut command = command_line.trim().to_string();
+    let mut steps = Vec::new();
+    let mut expanded: Vec<String> = Vec::new();
+
+    loop {
+        let (word, rest) = split_first_word(&command);
+
+        if let Some(alias) = get_alias_by_name(conn, word).ok().filter(|a| a.enabled) {
+            // The shell never expands an alias inside its own expansion
+            if let Some(position) = expanded.iter().position(|name| name == word) {
+                let stop = if position == expanded.len() - 1 {
+                    Stop::SelfReference(word.to_string())
+                } else {
+                    let mut cycle = expanded[position..].to_vec();
+                    cycle.push(word.to_string());
+                    Stop::Loop(cycle)
+                };
+                return Expansion {
+                    command,
+                    steps,
+                    stop,
+                };
+            }
+
+            if alias.command.contains('\n') {
+                steps.push(Step::Function {
+                    name: alias.name,
+                    body: alias.command,
+                });
+                return Expansion {
+                    command,
+                    steps,
+                    stop: Stop::Function,
+                };
+            }
+
+            command = format!("{}{}", alias.command.trim(), rest);
+            expanded.push(alias.name.clone());
+            steps.push(Step::Alias {
+                name: alias.name,
+                command: alias.command,
+            });
+            continue;
+        }
+
+        // Scripts are run by the name of their entry in the bin directory
+        let script = get_all_scripts(conn)
+            .into_iter()
+            .filter(|s| s.enabled)
+            .find(|s| bin_entry_name(s).is_some_and(|name| name == word));
+        let stop = match script {
+            Some(script) => {
+                steps.push(Step::Script {
+                    name: script.name,
+                    path: script.path,
+                });
+                Stop::Script
+            }
+            None => Stop::Command,
+        };
+        return Expansion {
+            command,
+            steps,
+            stop,
+        };
+    }
+}
+
+pub fn expand(ctx: &Context, command_line: &str) {
+    let expansion = expand_command(&ctx.conn, command_line);
+
+    if expansion.steps.is_empty() {
+        info!(format!(
+            "{} is not a nym alias or script",
+            style(split_first_word(command_line).0).bold()
+        ));
+    }
+
+    for step in &expansion.steps {
+        match step {
+            Step::Alias { name, command } => {
+                println!(
+                    "{} {} → {}",
+                    style("alias").cyan(),
+                    style(name).bold(),
+                    command
+                )
+            }
+            Step::Function { name, body } => {
+                println!(
+                    "{} {} → {}",
+                    style("function").blue(),
+                    style(name).bold(),
+                    command_preview(body)
+                )
+            }
+            Step::Script { name, path } => {
+                println!(
+                    "{} {} → {}",
+                    style("script").magenta(),
+                    style(name).bold(),
+                    path
+                )
+            }
+        }
+    }
+
+    match &expansion.stop {
+        Stop::SelfReference(name) => info!(format!(
+            "{} refers to itself, the shell runs the {} command",
+            style(name).bold(),
+            name
+        )),
+        Stop::Loop(cycle) => warning!(format!("Alias loop: {}", cycle.join(" -> "))),
+        Stop::Command | Stop::Function | Stop::Script => (),
+    }
+
+    println!("{}", style(&expansion.command).bold());
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_management::{
+        database::{aliases::add_alias, scripts::add_script, setupdb},
+        Alias, Script,
+    };
+
+    use super::*;
+
+    fn alias(name: &str, command: &str) -> Alias {
+        Alias {
+            name: name.to_string(),
+            command: command.to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id: 1,
+            after: None,
+        }
+    }
+
+    #[test]
+    fn expand_command_test() {
+        let db_file = "expand_command_test.db";
+        let conn = setupdb(db_file).unwrap();
+
+        add_alias(&conn, &alias("gpo", "gp origin")).unwrap();
+        add_alias(&conn, &alias("gp", "git push")).unwrap();
+        add_alias(&conn, &alias("ls", "ls --color")).unwrap();
+        add_alias(&conn, &alias("a", "b -x")).unwrap();
+        add_alias(&conn, &alias("b", "a -y")).unwrap();
+        add_alias(&conn, &alias("bk", "backup.sh --all")).unwrap();
+        add_alias(&conn, &alias("b2", "build")).unwrap();
+        add_alias(&conn, &alias("build", "cd src\nmake \"$@\"")).unwrap();
+        add_script(
+            &conn,
+            &Script::for_test("backup", "/scripts/backup/backup.sh"),
+        )
+        .unwrap();
+
+        let expansion = expand_command(&conn, "gpo main");
+        assert_eq!(expansion.command, "git push origin main");
+        assert_eq!(expansion.steps.len(), 2);
+        assert_eq!(expansion.stop, Stop::Command);
+
+        let expansion = expand_command(&conn, "ls -a");
+        assert_eq!(expansion.command, "ls --color -a");
+        assert_eq!(expansion.stop, Stop::SelfReference("ls".to_string()));
+
+        let expansion = expand_command(&conn, "a");
+        assert_eq!(expansion.command, "a -y -x");
+        assert_eq!(
+            expansion.stop,
+            Stop::Loop(vec!["a".to_string(), "b".to_string(), "a".to_string()])
+        );
+
+        let expansion = expand_command(&conn, "bk");
+        assert_eq!(expansion.command, "backup.sh --all");
+        assert_eq!(
+            expansion.steps[1],
+            Step::Script {
+                name: "backup".to_string(),
+                path: "/scripts/backup/backup.sh".to_string()
+            }
+        );
+        assert_eq!(expansion.stop, Stop::Script);
+
+        // The shell runs scripts by their file name, not the name nym knows them by
+        let expansion = expand_command(&conn, "backup.sh");
+        assert_eq!(expansion.steps.len(), 1);
+        assert_eq!(expansion.stop, Stop::Script);
+        let expansion = expand_command(&conn, "backup");
+        assert!(expansion.steps.is_empty());
+        assert_eq!(expansion.stop, Stop::Command);
+
+        let expansion = expand_command(&conn, "b2 -j4");
+        assert_eq!(expansion.command, "build -j4");
+        assert_eq!(
+            expansion.steps[1],
+            Step::Function {
+                name: "build".to_string(),
+                body: "cd src\nmake \"$@\"".to_string()
+            }
+        );
+        assert_eq!(expansion.stop, Stop::Function);
+
+        let expansion = expand_command(&conn, "  echo hi ");
+        assert_eq!(expansion.command, "echo hi");
+        assert!(expansion.steps.is_empty());
+
+        std::fs::remove_file(db_file).expect("Error cleaning up test files");
+    }
+}
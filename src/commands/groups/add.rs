@@ -1,18 +1,14 @@
 use console::style;
 
 use crate::{
+    commands::Context,
     error,
-    file_management::database::{
-        db_conn,
-        groups::{create_group, get_group_by_name},
-    },
+    file_management::database::groups::{create_group, get_group_by_name},
     success,
 };
 
-pub fn add_group(db_file: &str, group_name: &str) {
-    let conn = db_conn(db_file);
-
-    if get_group_by_name(&conn, group_name).is_ok() {
+pub fn add_group(ctx: &Context, group_name: &str) {
+    if get_group_by_name(&ctx.conn, group_name).is_ok() {
         error!(format!(
             "Group with name {} already exists",
             style(group_name).bold()
@@ -20,7 +16,7 @@ pub fn add_group(db_file: &str, group_name: &str) {
         return;
     }
 
-    create_group(&conn, group_name);
+    create_group(&ctx.conn, group_name);
     success!(format!("Group {} created successfully", group_name));
 }
 
@@ -34,14 +30,14 @@ mod tests {
     fn add_group_command_test() {
         let db_path = "add_group_command_test.db";
 
-        let conn = db_conn(db_path);
-        add_group(db_path, "Testgroup1");
+        let ctx = Context::new("", db_path).unwrap();
+        add_group(&ctx, "Testgroup1");
 
-        let group = get_group_by_name(&conn, "Testgroup1").unwrap();
+        let group = get_group_by_name(&ctx.conn, "Testgroup1").unwrap();
 
         assert_eq!(group.name, "Testgroup1");
 
-        let mut group_vec = get_groups(&conn);
+        let mut group_vec = get_groups(&ctx.conn);
         group_vec.sort();
 
         assert_eq!(
@@ -1,10 +1,11 @@
-use crate::file_management::database::{db_conn, groups::get_groups};
+use crate::{
+    commands::{aliases::list::command_preview, scripts::list::script_notes, Context},
+    file_management::database::groups::get_groups,
+};
 
 use console::style;
-pub fn list_groups(db_file: &str) {
-    let conn = db_conn(db_file);
-
-    let groups = get_groups(&conn);
+pub fn list_groups(ctx: &Context) {
+    let groups = get_groups(&ctx.conn);
 
     for group in groups {
         println!("{}", style(group.name).bold());
@@ -20,10 +21,8 @@ pub fn list_groups(db_file: &str) {
     }
 }
 
-pub fn list_all(db_file: &str) {
-    let conn = db_conn(db_file);
-
-    let groups = get_groups(&conn);
+pub fn list_all(ctx: &Context) {
+    let groups = get_groups(&ctx.conn);
 
     for group in groups {
         println!("{}:", style(group.name).bold().underlined());
@@ -37,13 +36,13 @@ pub fn list_all(db_file: &str) {
                     println!(
                         "\t✅ {}-> {}",
                         style(alias.name).green(),
-                        style(alias.command).green()
+                        style(command_preview(&alias.command)).green()
                     );
                 } else {
                     println!(
                         "\t❌ {} -> {} ",
                         style(alias.name).red(),
-                        style(alias.command).red()
+                        style(command_preview(&alias.command)).red()
                     );
                 }
             }
@@ -59,9 +58,13 @@ pub fn list_all(db_file: &str) {
                     .unwrap();
 
                 if script.enabled {
-                    println!("\t✅ {}", style(script_file).green());
+                    println!(
+                        "\t✅ {}{}",
+                        style(script_file).green(),
+                        script_notes(&script)
+                    );
                 } else {
-                    println!("\t❌ {}", style(script_file).red());
+                    println!("\t❌ {}{}", style(script_file).red(), script_notes(&script));
                 }
             }
         }
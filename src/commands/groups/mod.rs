@@ -6,18 +6,12 @@ pub mod toggle;
 
 use crate::error;
 use crate::file_management::database::groups::get_groups;
-use crate::file_management::{database::setupdb, Group};
+use crate::file_management::Group;
+use rusqlite::Connection;
 
-pub fn fuzzy_get_group(db_path: &str, name: &str) -> Option<Group> {
+pub fn fuzzy_get_group(conn: &Connection, name: &str) -> Option<Group> {
     // A function to get an alias by name, but also get the closest match if the name doesn't exist
-    let conn = match setupdb(db_path) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to database");
-            return None;
-        }
-    };
-    let groups = get_groups(&conn);
+    let groups = get_groups(conn);
     let mut closest_match: Option<Group> = None;
     let mut closest_distance = usize::MAX;
 
@@ -36,8 +30,8 @@ pub fn fuzzy_get_group(db_path: &str, name: &str) -> Option<Group> {
     closest_match
 }
 
-pub fn ask_fuzzy_get(db_path: &str, name: &str) -> Option<Group> {
-    let group = match fuzzy_get_group(db_path, name) {
+pub fn ask_fuzzy_get(conn: &Connection, name: &str) -> Option<Group> {
+    let group = match fuzzy_get_group(conn, name) {
         Some(group) => group,
         None => {
             error!("Group not found");
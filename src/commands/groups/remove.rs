@@ -1,20 +1,14 @@
 use console::style;
 
 use crate::{
-    error,
-    file_management::{
-        database::{db_conn, groups::remove_group as remove_group_database},
-        update_runcom,
-    },
-    success,
+    commands::Context, error,
+    file_management::database::groups::remove_group as remove_group_database, success,
 };
 
 use super::fuzzy_get_group;
 
-pub fn remove_group(runcom_file: &str, db_file: &str, group_name: &str, force: bool) {
-    let conn = db_conn(db_file);
-
-    let group = match fuzzy_get_group(db_file, group_name) {
+pub fn remove_group(ctx: &Context, group_name: &str, force: bool) {
+    let group = match fuzzy_get_group(&ctx.conn, group_name) {
         Some(group) => group,
         None => {
             error!("Group not found");
@@ -40,7 +34,7 @@ pub fn remove_group(runcom_file: &str, db_file: &str, group_name: &str, force: b
         std::process::exit(1);
     }
 
-    match remove_group_database(&conn, &group.name) {
+    match ctx.commit(|tx| remove_group_database(tx, &group.name)) {
         Ok(_) => success!(format!(
             "Group {} successfully deleted",
             style(group.name).bold()
@@ -51,11 +45,8 @@ pub fn remove_group(runcom_file: &str, db_file: &str, group_name: &str, force: b
                 style(group.name).bold(),
                 err
             ));
-            return;
         }
     };
-
-    update_runcom(runcom_file, db_file);
 }
 
 #[cfg(test)]
@@ -76,16 +67,16 @@ mod tests {
         let rc_file = "remove_group_test_rc";
         let group_name = "testgroup1";
 
-        let conn = db_conn(db_file);
+        let ctx = Context::new(rc_file, db_file).unwrap();
 
-        add_group(db_file, group_name);
+        add_group(&ctx, group_name);
 
-        let group = get_group_by_name(&conn, group_name).unwrap();
+        let group = get_group_by_name(&ctx.conn, group_name).unwrap();
 
         assert_eq!(group.name, group_name);
 
-        remove_group(rc_file, db_file, group_name, true);
-        let group_vec = get_groups(&conn);
+        remove_group(&ctx, group_name, true);
+        let group_vec = get_groups(&ctx.conn);
         assert_eq!(
             group_vec,
             vec![Group {
@@ -1,52 +1,45 @@
 use crate::{
+    commands::Context,
     error,
-    file_management::{
-        database::{db_conn, groups::edit_group},
-        update_runcom,
-    },
+    file_management::database::groups::{edit_group, get_group_by_name},
     success,
 };
 use console::style;
 
-pub fn rename_group(runcom_file: &str, db_file: &str, old_name: &str, new_name: &str) {
+pub fn rename_group(ctx: &Context, old_name: &str, new_name: &str) {
     if old_name == "uncategorized" {
         error!("Cannot rename uncategorized group");
         return;
     }
 
-    let conn = db_conn(db_file);
-
-    let mut group =
-        match crate::file_management::database::groups::get_group_by_name(&conn, old_name) {
-            Ok(group) => group,
-            Err(_) => {
-                error!(format!("No group with name {}", style(old_name).bold()));
-                return;
-            }
-        };
+    let mut group = match get_group_by_name(&ctx.conn, old_name) {
+        Ok(group) => group,
+        Err(_) => {
+            error!(format!("No group with name {}", style(old_name).bold()));
+            return;
+        }
+    };
 
     group.name = new_name.to_string();
-    match edit_group(&conn, old_name, group) {
+    match ctx.commit(|tx| edit_group(tx, old_name, group).map_err(String::from)) {
         Ok(_) => {
             success!("Group successfully renamed");
         }
-        Err(_) => {
-            error!(format!("could not rename group {}", style(old_name).bold()));
-            return;
+        Err(err) => {
+            error!(format!(
+                "could not rename group {} - {}",
+                style(old_name).bold(),
+                err
+            ));
         }
     };
-
-    update_runcom(runcom_file, db_file);
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
         commands::groups::add::add_group,
-        file_management::{
-            database::groups::{get_group_by_name, get_groups},
-            Group,
-        },
+        file_management::{database::groups::get_groups, Group},
     };
 
     use super::*;
@@ -57,16 +50,16 @@ mod tests {
         let rc_file = "rename_group_test_rc";
         let group_name = "testgroup1";
 
-        let conn = db_conn(db_file);
+        let ctx = Context::new(rc_file, db_file).unwrap();
 
-        add_group(db_file, group_name);
+        add_group(&ctx, group_name);
 
-        let group = get_group_by_name(&conn, group_name).unwrap();
+        let group = get_group_by_name(&ctx.conn, group_name).unwrap();
         assert_eq!(group.name, group_name);
 
-        rename_group(rc_file, db_file, group_name, "newgroupname");
+        rename_group(&ctx, group_name, "newgroupname");
 
-        let mut group_vec = get_groups(&conn);
+        let mut group_vec = get_groups(&ctx.conn);
         group_vec.sort();
         assert_eq!(
             group_vec,
@@ -1,11 +1,39 @@
 use console::style;
+use rusqlite::Connection;
 
-use crate::{error, success};
+use crate::{
+    commands::{scripts::schedule::apply_schedules, Context},
+    error,
+    file_management::{
+        database::{aliases::update_alias, scripts::update_script},
+        Group,
+    },
+    success,
+};
 
 use super::fuzzy_get_group;
 
-pub fn toggle_group(runcom_file: &str, db_file: &str, group_name: &str) {
-    let group = match fuzzy_get_group(db_file, group_name) {
+/// Enable or disable every alias and script in a group
+pub fn set_group_enabled(conn: &Connection, group: &Group, enabled: bool) -> Result<(), String> {
+    for alias in group.aliases.iter().filter(|a| a.enabled != enabled) {
+        let mut alias = alias.clone();
+        alias.enabled = enabled;
+        update_alias(conn, &alias.name.clone(), alias)?;
+    }
+    for script in group.scripts.iter().filter(|s| s.enabled != enabled) {
+        let mut script = script.clone();
+        script.enabled = enabled;
+        update_script(conn, &script.name, &script)?;
+    }
+    Ok(())
+}
+
+pub fn group_enabled(group: &Group) -> bool {
+    group.aliases.iter().all(|a| a.enabled) && group.scripts.iter().all(|s| s.enabled)
+}
+
+pub fn toggle_group(ctx: &Context, group_name: &str) {
+    let group = match fuzzy_get_group(&ctx.conn, group_name) {
         Some(g) => g,
         None => {
             error!(format!("Could not find group {}", style(group_name).bold()));
@@ -13,31 +41,117 @@ pub fn toggle_group(runcom_file: &str, db_file: &str, group_name: &str) {
         }
     };
 
-    let enabled =
-        group.aliases.iter().all(|a| a.enabled) && group.scripts.iter().all(|g| g.enabled);
+    let enabled = group_enabled(&group);
 
-    if enabled {
-        for alias in group.aliases {
-            crate::commands::aliases::edit::toggle_alias(runcom_file, db_file, &alias.name);
-        }
-        for script in group.scripts {
-            crate::commands::scripts::edit::toggle_script(runcom_file, db_file, &script.name);
-        }
-    } else {
-        for alias in group.aliases {
-            if !alias.enabled {
-                crate::commands::aliases::edit::toggle_alias(runcom_file, db_file, &alias.name);
-            }
-        }
-        for script in group.scripts {
-            if !script.enabled {
-                crate::commands::scripts::edit::toggle_script(runcom_file, db_file, &script.name);
+    match ctx.commit(|tx| set_group_enabled(tx, &group, !enabled)) {
+        Ok(_) => {
+            success!(format!(
+                "Group {} toggled {}",
+                style(&group.name).bold(),
+                if enabled { "off" } else { "on" }
+            ));
+            // Disabled scripts don't run on their schedule
+            if group.scripts.iter().any(|s| s.schedule.is_some()) {
+                if let Err(err) = apply_schedules(ctx) {
+                    error!(err);
+                }
             }
         }
+        Err(err) => error!(format!(
+            "could not toggle group {} - {}",
+            style(group.name).bold(),
+            err
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::{
+            aliases::add::add_alias,
+            groups::add::add_group,
+            scripts::{add::add_script, check::FixIssues, schedule::scheduled_scripts},
+        },
+        file_management::{
+            database::{aliases::get_all_aliases, scripts::get_script_by_name},
+            runcom::read_aliases,
+            Script,
+        },
+    };
+
+    use super::*;
+
+    #[test]
+    fn toggle_group_test() {
+        let db_file = "toggle_group_test.db";
+        let rc_file = "toggle_group_test_rc";
+
+        let ctx = Context::new(rc_file, db_file).unwrap();
+        add_group(&ctx, "testgroup");
+        add_alias(&ctx, r#"test1="echo 1""#, "", 2, None, false);
+        add_alias(&ctx, r#"test2="echo 2""#, "", 2, None, false);
+        add_alias(&ctx, r#"test3="echo 3""#, "", 1, None, false);
+
+        toggle_group(&ctx, "testgroup");
+        let enabled: Vec<(String, bool)> = get_all_aliases(&ctx.conn)
+            .into_iter()
+            .map(|a| (a.name, a.enabled))
+            .collect();
+        assert_eq!(
+            enabled,
+            vec![
+                ("test1".to_string(), false),
+                ("test2".to_string(), false),
+                ("test3".to_string(), true)
+            ]
+        );
+        let rc_aliases = read_aliases(rc_file).unwrap();
+        assert_eq!(rc_aliases.len(), 1);
+        assert_eq!(rc_aliases[0].name, "test3");
+
+        toggle_group(&ctx, "testgroup");
+        assert!(get_all_aliases(&ctx.conn).iter().all(|a| a.enabled));
+        assert_eq!(read_aliases(rc_file).unwrap().len(), 3);
+
+        std::fs::remove_file(db_file).expect("Error cleaning up test files");
+        std::fs::remove_file(rc_file).expect("Error cleaning up test files");
+        std::fs::remove_file(format!("{}.bak", rc_file)).expect("Error cleaning up test files");
+    }
+
+    #[test]
+    fn toggle_scheduled_group_test() {
+        let test_dir = "toggle_scheduled_group_test";
+        let db_file = "toggle_scheduled_group_test/nym.db";
+        let rc_file = "toggle_scheduled_group_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+        add_group(&ctx, "testgroup");
+
+        let source = "toggle_scheduled_group_test/nightly.sh";
+        std::fs::write(source, "#!/bin/sh\necho nightly\n").unwrap();
+        add_script(&ctx, source, "", Some(2), false, false, FixIssues::Always);
+        let script = get_script_by_name(&ctx.conn, "nightly").unwrap();
+        let scheduled = Script {
+            schedule: Some("daily".to_string()),
+            ..script.clone()
+        };
+        ctx.commit(|tx| update_script(tx, &script.name, &scheduled).map_err(String::from))
+            .unwrap();
+        let expected = vec![("nightly".to_string(), "daily".to_string())];
+        assert_eq!(scheduled_scripts(&ctx.conn), expected);
+
+        // toggle_group itself would install the timers on this machine
+        let group = fuzzy_get_group(&ctx.conn, "testgroup").unwrap();
+        ctx.commit(|tx| set_group_enabled(tx, &group, false))
+            .unwrap();
+        assert!(scheduled_scripts(&ctx.conn).is_empty());
+
+        let group = fuzzy_get_group(&ctx.conn, "testgroup").unwrap();
+        ctx.commit(|tx| set_group_enabled(tx, &group, true))
+            .unwrap();
+        assert_eq!(scheduled_scripts(&ctx.conn), expected);
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
     }
-    success!(format!(
-        "Group {} toggled {}",
-        style(group.name).bold(),
-        if enabled { "off" } else { "on" }
-    ))
 }
@@ -1,53 +1,76 @@
 pub mod aliases;
+pub mod collisions;
+pub mod doctor;
+pub mod exec;
+pub mod expand;
 pub mod groups;
 pub mod scripts;
 
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 
-use crate::file_management::{
-    database::{aliases::get_all_aliases, groups::get_group_nameids, scripts::get_all_scripts},
-    Alias, Group, Script,
-};
+use crate::file_management::{commit_changes, database::setupdb, Alias, Group, Script};
+use crate::helpers::shell::Shell;
 use console::style;
 use rusqlite::Connection;
 
-#[derive(Clone)]
-pub enum Item {
-    Alias(Alias),
-    Group(Group),
-    Script(Script),
+/// State shared by every command run during a single nym invocation
+pub struct Context {
+    pub conn: Connection,
+    pub rc_file: String,
+    pub db_file: String,
+    /// Shell the runcom is written for
+    pub shell: Shell,
 }
 
-pub fn get_groups_and_aliases(conn: &Connection) -> Vec<Group> {
-    let aliases = get_all_aliases(conn);
-    let scripts = get_all_scripts(conn);
-    let mut groups = match get_group_nameids(conn) {
-        Ok(group_ids) => group_ids,
-        Err(_) => return Vec::new(),
-    };
-
-    for alias in aliases {
-        if let Some(group) = groups.iter_mut().find(|g| g.id == alias.group_id) {
-            group.aliases.push(alias);
-        }
+impl Context {
+    pub fn new(rc_file: &str, db_file: &str) -> Result<Context, String> {
+        Ok(Context {
+            conn: setupdb(db_file)?,
+            rc_file: rc_file.to_string(),
+            db_file: db_file.to_string(),
+            shell: Shell::detect(),
+        })
     }
 
-    for script in scripts {
-        if let Some(group) = groups.iter_mut().find(|g| g.id == script.group_id) {
-            group.scripts.push(script);
-        }
+    /// Directory the database and everything else nym manages is kept in
+    pub fn nym_dir(&self) -> &Path {
+        Path::new(&self.db_file).parent().unwrap_or(Path::new(""))
     }
 
-    groups
+    /// Directory managed copies of scripts are kept in
+    pub fn scripts_dir(&self) -> PathBuf {
+        self.nym_dir().join("scripts")
+    }
+
+    /// Directory the venvs of Python scripts are kept in
+    pub fn venvs_dir(&self) -> PathBuf {
+        self.nym_dir().join("venvs")
+    }
+
+    /// Apply `changes` in one transaction and regenerate the runcom, see [`commit_changes`]
+    pub fn commit<T, F>(&self, changes: F) -> Result<T, String>
+    where
+        F: FnOnce(&Connection) -> Result<T, String>,
+    {
+        commit_changes(&self.conn, &self.rc_file, self.shell, changes)
+    }
+}
+
+#[derive(Clone)]
+pub enum Item {
+    Alias(Alias),
+    Group(Group),
+    Script(Script),
 }
 
-pub fn get_item(db_path: &str, name: &str, include_groups: bool) -> Option<Item> {
+pub fn get_item(conn: &Connection, name: &str, include_groups: bool) -> Option<Item> {
     // Fuzzy get item, if not found, return None
     // If multiple exist ask which one should be returned
     let mut item_map = HashMap::new();
-    let alias = aliases::fuzzy_get_alias(name, db_path);
-    let group = groups::fuzzy_get_group(db_path, name);
-    let script = scripts::fuzzy_get_script(db_path, name);
+    let alias = aliases::fuzzy_get_alias(conn, name);
+    let group = groups::fuzzy_get_group(conn, name);
+    let script = scripts::fuzzy_get_script(conn, name);
 
     if let Some(a) = alias {
         if a.name == name {
@@ -1,29 +1,107 @@
+use std::path::Path;
+
+use super::{
+    check::{review_issues, FixIssues},
+    header::{check_requires, header_group_id, read_header},
+    history::{hash_content, record_version},
+    interpreter::detect_script_type,
+    launcher::prepare_script,
+};
 use crate::{
+    commands::{collisions::check_script_name, Context},
     error,
-    file_management::{
-        database::{
-            db_conn,
-            scripts::{add_script as add_script_to_database, get_script_by_name},
-        },
-        update_runcom, Script,
-    },
+    file_management::{database::scripts::add_script as add_script_to_database, Script},
     success,
 };
 
+#[cfg(unix)]
+pub(super) fn link_script(target: &Path, link: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, link)
+}
+
+#[cfg(not(unix))]
+pub(super) fn link_script(_target: &Path, _link: &Path) -> std::io::Result<()> {
+    Err(std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        "linking scripts is only supported on unix",
+    ))
+}
+
+/// Entry for a script just stored at `stored_path`, the description and group are read from its
+/// header when they aren't given
+pub(super) fn script_entry(
+    ctx: &Context,
+    name: &str,
+    stored_path: &Path,
+    description: &str,
+    group_id: Option<i32>,
+) -> Script {
+    let header = read_header(stored_path);
+    check_requires(ctx, &header);
+    let description = match (description, &header.description) {
+        ("", Some(header_description)) => header_description.clone(),
+        _ => description.to_string(),
+    };
+
+    Script {
+        name: name.to_string(),
+        path: stored_path.to_str().unwrap().to_string(),
+        description,
+        enabled: true,
+        group_id: group_id
+            .or_else(|| header_group_id(ctx, &header))
+            .unwrap_or(1),
+        source_path: None,
+        source_hash: None,
+        linked: false,
+        entry_point: None,
+        type_: detect_script_type(stored_path),
+        issues: Vec::new(),
+        usage: header.usage.unwrap_or_default(),
+        log_runs: false,
+        schedule: None,
+    }
+}
+
+/// Save a newly stored script along with its first version, then set up its venv and launcher
+///
+/// The script's directory and venv are removed again if anything fails.
+pub(super) fn register_script(
+    ctx: &Context,
+    script: &Script,
+    message: &str,
+    requirements_dir: Option<&Path>,
+) -> Result<(), String> {
+    // Add script to database and update runcom file
+    let result = ctx.commit(|tx| {
+        add_script_to_database(tx, script)?;
+        record_version(tx, script, Path::new(&script.path), message)?;
+        prepare_script(ctx, script, requirements_dir)
+    });
+    if result.is_err() {
+        if let Some(script_dir) = Path::new(&script.path).parent() {
+            let _ = std::fs::remove_dir_all(script_dir);
+        }
+        let _ = std::fs::remove_dir_all(ctx.venvs_dir().join(&script.name));
+    }
+    result
+}
+
+/// Add a script to nym, copying it into the scripts directory or, with `link`, symlinking it so
+/// nym always runs the file at `script_path`
+///
+/// The description and group are read from the script's header when they aren't given. A file
+/// that isn't executable or has no `#!` line is fixed as `fix` says, any issues left are
+/// stored so `list scripts` can point them out.
 pub fn add_script(
-    rc_file: &str,
-    db_file: &str,
+    ctx: &Context,
     script_path: &str,
     description: &str,
-    group_id: i32,
+    group_id: Option<i32>,
+    strict: bool,
+    link: bool,
+    fix: FixIssues,
 ) {
-    let conn = db_conn(db_file);
-    // Check if script exists
-    if get_script_by_name(&conn, script_path).is_ok() {
-        error!("Script already exists");
-        return;
-    }
-
     // get script name from path
     let script_name = script_path
         .split('/')
@@ -32,11 +110,12 @@ pub fn add_script(
         .unwrap()
         .to_string();
 
-    let db_path = std::path::Path::new(db_file);
-    let parent_dir = db_path.parent().unwrap();
-
-    let scripts_dir = parent_dir.join("scripts");
+    let scripts_dir = ctx.scripts_dir();
     let script_name_no_ext = script_name.split('.').collect::<Vec<&str>>()[0];
+    if !check_script_name(ctx, script_name_no_ext, strict) {
+        return;
+    }
+
     if !scripts_dir.exists() && std::fs::create_dir(&scripts_dir).is_err() {
         error!("Issue creating scripts directory");
         return;
@@ -48,45 +127,56 @@ pub fn add_script(
         return;
     }
 
-    // Copy script to scripts directory
-    if std::fs::copy(
-        script_path,
-        scripts_dir
-            .join(script_name_no_ext)
-            .join(script_name.clone()),
-    )
-    .is_err()
-    {
+    let source_path = std::fs::canonicalize(script_path)
+        .ok()
+        .and_then(|path| path.to_str().map(String::from));
+    let stored_path = scripts_dir.join(script_name_no_ext).join(&script_name);
+
+    if link {
+        // Link to the absolute path so the link works from the scripts directory
+        let linked = match &source_path {
+            Some(source) => link_script(Path::new(source), &stored_path),
+            None => Err(std::io::Error::from(std::io::ErrorKind::NotFound)),
+        };
+        if let Err(err) = linked {
+            error!(format!(
+                "Issue linking script into scripts directory: {}",
+                err
+            ));
+            let _ = std::fs::remove_dir_all(scripts_dir.join(script_name_no_ext));
+            return;
+        }
+    } else if std::fs::copy(script_path, &stored_path).is_err() {
         error!("Issue copying script to scripts directory");
         return;
     }
 
-    let script = Script {
-        name: script_name_no_ext.to_string(),
-        path: scripts_dir
-            .join(script_name_no_ext)
-            .join(script_name.clone())
-            .to_str()
-            .unwrap()
-            .to_string(),
-        description: description.to_string(),
-        enabled: true,
-        group_id,
+    // Linked scripts are their source, there is nothing to fall behind
+    let source_hash = match std::fs::read(&stored_path) {
+        Ok(content) if !link => Some(hash_content(&content)),
+        _ => None,
     };
+    let mut script = Script {
+        source_path,
+        source_hash,
+        linked: link,
+        ..script_entry(ctx, script_name_no_ext, &stored_path, description, group_id)
+    };
+    script.issues = review_issues(&script, &stored_path, fix);
 
-    // Add script to database
-    if add_script_to_database(&conn, &script).is_err() {
-        error!("Issue adding script to database");
+    let message = format!("Added from {}", script_path);
+    if let Err(err) = register_script(ctx, &script, &message, Path::new(script_path).parent()) {
+        error!(format!("Issue adding script: {}", err));
         return;
     }
 
-    // Update runcom file
-    update_runcom(rc_file, db_file);
     success!("Script added successfully");
 }
 
 #[cfg(test)]
 mod tests {
+    use crate::file_management::database::scripts::get_script_by_name;
+
     use super::*;
 
     #[test]
@@ -98,7 +188,7 @@ mod tests {
         // create test folder
         assert!(std::fs::create_dir(test_dir).is_ok());
 
-        let conn = db_conn(db_file);
+        let ctx = Context::new(rc_file, db_file).unwrap();
         assert!(std::path::Path::new(db_file).exists());
 
         // Create test script
@@ -106,9 +196,9 @@ mod tests {
         let script = r#"echo "test script""#;
         std::fs::write(script_path, script).expect("Error creating test script");
 
-        add_script(rc_file, db_file, script_path, "", 1);
+        add_script(&ctx, script_path, "", None, false, false, FixIssues::Never);
 
-        let script = get_script_by_name(&conn, "test_script");
+        let script = get_script_by_name(&ctx.conn, "test_script");
         assert!(script.is_ok());
         assert_eq!(script.clone().unwrap().name, "test_script");
         assert_eq!(
@@ -116,6 +206,42 @@ mod tests {
             "add_script_test/scripts/test_script/test_script.sh"
         );
 
+        // The description and usage come from the header when not given
+        let header_path = "add_script_test/header_script.sh";
+        std::fs::write(
+            header_path,
+            "#!/bin/sh\n# Description: Say hello\n# Usage: header_script <name>\necho hello\n",
+        )
+        .unwrap();
+        add_script(&ctx, header_path, "", None, false, false, FixIssues::Never);
+        let script = get_script_by_name(&ctx.conn, "header_script").unwrap();
+        assert_eq!(script.description, "Say hello");
+        assert_eq!(script.usage, "header_script <name>");
+        assert_eq!(script.group_id, 1);
+
+        // Issues in a linked script are recorded, its original file is left as it was
+        #[cfg(unix)]
+        {
+            use crate::file_management::ScriptIssue;
+            use std::os::unix::fs::PermissionsExt;
+
+            let linked_path = "add_script_test/linked_script.sh";
+            std::fs::write(linked_path, "echo linked\n").unwrap();
+            std::fs::set_permissions(linked_path, std::fs::Permissions::from_mode(0o644)).unwrap();
+            add_script(&ctx, linked_path, "", None, false, true, FixIssues::Always);
+            let script = get_script_by_name(&ctx.conn, "linked_script").unwrap();
+            assert_eq!(
+                script.issues,
+                vec![ScriptIssue::NotExecutable, ScriptIssue::NoShebang]
+            );
+            assert_eq!(
+                std::fs::read_to_string(linked_path).unwrap(),
+                "echo linked\n"
+            );
+            let mode = std::fs::metadata(linked_path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o111, 0);
+        }
+
         // Clean up
         std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
         std::fs::remove_file(script_path).expect("Error cleaning up test files");
@@ -0,0 +1,269 @@
+use std::{
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use console::style;
+
+use super::project::project_dir;
+use crate::{
+    file_management::{Script, ScriptIssue, ScriptType},
+    helpers::questions::yesno,
+    info, warning,
+};
+
+/// Something that stops a script from running
+#[derive(Debug, PartialEq)]
+pub enum ScriptProblem {
+    /// The stored file is gone
+    Missing,
+    /// The script is linked to a file that no longer exists
+    DanglingLink(PathBuf),
+    /// The file a project's launcher runs is gone
+    MissingEntryPoint(String),
+}
+
+impl ScriptProblem {
+    pub fn describe(&self) -> String {
+        match self {
+            ScriptProblem::Missing => "script file is missing".to_string(),
+            ScriptProblem::DanglingLink(target) => {
+                format!("links to {}, which does not exist", target.display())
+            }
+            ScriptProblem::MissingEntryPoint(entry_point) => {
+                format!("entry point {} is missing", entry_point)
+            }
+        }
+    }
+}
+
+/// Whether to fix the issues found in a script
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FixIssues {
+    /// Ask about each one
+    Ask,
+    Always,
+    /// Only record them
+    Never,
+}
+
+pub fn describe_issue(issue: ScriptIssue) -> &'static str {
+    match issue {
+        ScriptIssue::NotExecutable => "not executable",
+        ScriptIssue::NoShebang => "no #! line",
+    }
+}
+
+/// Issues stopping the file at `path` from running on its own
+pub fn find_issues(path: &Path) -> Vec<ScriptIssue> {
+    let mut issues = Vec::new();
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        match std::fs::metadata(path) {
+            Ok(metadata) if metadata.permissions().mode() & 0o111 == 0 => {
+                issues.push(ScriptIssue::NotExecutable)
+            }
+            _ => (),
+        }
+    }
+
+    // Binaries run without a #! line, text files need one
+    let mut start = [0; 4];
+    let read = std::fs::File::open(path)
+        .and_then(|mut file| file.read(&mut start))
+        .unwrap_or(0);
+    let start = &start[..read];
+    let binary = start.starts_with(b"\x7fELF")
+        || start.starts_with(&[0xcf, 0xfa, 0xed, 0xfe])
+        || start.starts_with(&[0xca, 0xfe, 0xba, 0xbe])
+        || start.starts_with(b"MZ");
+    if read > 0 && !binary && !start.starts_with(b"#!") {
+        issues.push(ScriptIssue::NoShebang);
+    }
+    issues
+}
+
+/// `#!` line for a script of `type_`, `None` if nym can't tell what runs it
+fn shebang(type_: ScriptType) -> Option<String> {
+    type_
+        .interpreter()
+        .map(|interpreter| format!("#!/usr/bin/env {}\n", interpreter))
+}
+
+/// Fix `issue` in the file at `path`, which holds a script of `type_`
+pub fn fix_issue(path: &Path, type_: ScriptType, issue: ScriptIssue) -> Result<(), String> {
+    match issue {
+        ScriptIssue::NotExecutable => {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mut permissions = std::fs::metadata(path)
+                    .map_err(|err| format!("Could not read {}: {}", path.display(), err))?
+                    .permissions();
+                // Execute for everyone who can read it, like `chmod +x`
+                let mode = permissions.mode();
+                permissions.set_mode(mode | ((mode & 0o444) >> 2));
+                std::fs::set_permissions(path, permissions).map_err(|err| {
+                    format!("Could not make {} executable: {}", path.display(), err)
+                })?;
+            }
+            Ok(())
+        }
+        ScriptIssue::NoShebang => {
+            let shebang = shebang(type_).ok_or(format!(
+                "Could not tell what runs {}, add a #! line to it",
+                path.display()
+            ))?;
+            let content = std::fs::read(path)
+                .map_err(|err| format!("Could not read {}: {}", path.display(), err))?;
+            let mut fixed = shebang.into_bytes();
+            fixed.extend(content);
+            // Writing in place keeps the file's permissions
+            std::fs::write(path, fixed)
+                .map_err(|err| format!("Could not write {}: {}", path.display(), err))
+        }
+    }
+}
+
+/// Check the file at `path` of a script being added or updated, fixing issues as `fix` says
+///
+/// Returns the issues left so they can be stored with the script. Projects run through their
+/// launcher so they aren't checked. Fixing a linked script changes the file it links to, so that
+/// is only done when asked about and never for `FixIssues::Always`.
+pub fn review_issues(script: &Script, path: &Path, fix: FixIssues) -> Vec<ScriptIssue> {
+    if script.entry_point.is_some() {
+        return Vec::new();
+    }
+
+    let mut remaining = Vec::new();
+    for issue in find_issues(path) {
+        let message = format!(
+            "Script {}: {}",
+            style(&script.name).bold(),
+            describe_issue(issue)
+        );
+        let fixing = match fix {
+            FixIssues::Never => false,
+            FixIssues::Always if script.linked => false,
+            FixIssues::Ask if script.linked => yesno!(format!(
+                "{}, fix it? This changes the original file {}",
+                message,
+                script.source_path.as_deref().unwrap_or(&script.path)
+            ))
+            .unwrap_or(false),
+            FixIssues::Always => true,
+            FixIssues::Ask => yesno!(format!("{}, fix it?", message)).unwrap_or(false),
+        };
+        if !fixing {
+            warning!(message);
+            remaining.push(issue);
+        } else if let Err(err) = fix_issue(path, script.type_, issue) {
+            warning!(err);
+            remaining.push(issue);
+        } else {
+            info!(format!(
+                "Fixed script {}: {}",
+                style(&script.name).bold(),
+                describe_issue(issue)
+            ));
+        }
+    }
+    remaining
+}
+
+/// Problem with a file or directory nym stores, following it if it is a link
+fn check_stored(path: &Path) -> Option<ScriptProblem> {
+    match std::fs::symlink_metadata(path) {
+        Err(_) => Some(ScriptProblem::Missing),
+        // metadata follows the link, so it fails when the target is gone
+        Ok(metadata) if metadata.file_type().is_symlink() && std::fs::metadata(path).is_err() => {
+            let target = std::fs::read_link(path).unwrap_or_default();
+            Some(ScriptProblem::DanglingLink(target))
+        }
+        Ok(_) => None,
+    }
+}
+
+/// Problems with a script's stored file, or for a project its launcher and directory
+pub fn check_script(script: &Script) -> Vec<ScriptProblem> {
+    let mut problems = Vec::new();
+    problems.extend(check_stored(Path::new(&script.path)));
+
+    if let Some(entry_point) = &script.entry_point {
+        let project = project_dir(script);
+        match check_stored(&project) {
+            Some(problem) => problems.push(problem),
+            None if !project.join(entry_point).is_file() => {
+                problems.push(ScriptProblem::MissingEntryPoint(entry_point.clone()))
+            }
+            None => (),
+        }
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_management::ScriptType;
+
+    use super::*;
+
+    #[test]
+    fn check_script_test() {
+        let test_dir = Path::new("check_script_test");
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let script = |file: &str| Script::for_test("check", test_dir.join(file).to_str().unwrap());
+
+        std::fs::write(test_dir.join("ok.sh"), "echo ok").unwrap();
+        assert_eq!(check_script(&script("ok.sh")), vec![]);
+        assert_eq!(
+            check_script(&script("missing.sh")),
+            vec![ScriptProblem::Missing]
+        );
+
+        #[cfg(unix)]
+        {
+            let target = test_dir.join("gone.sh");
+            std::os::unix::fs::symlink(&target, test_dir.join("dangling.sh")).unwrap();
+            assert_eq!(
+                check_script(&script("dangling.sh")),
+                vec![ScriptProblem::DanglingLink(target)]
+            );
+        }
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+
+    #[test]
+    fn fix_issues_test() {
+        let test_dir = Path::new("fix_issues_test");
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let script = test_dir.join("tool.py");
+        std::fs::write(&script, "print('tool')\n").unwrap();
+        let binary = test_dir.join("tool");
+        std::fs::write(&binary, b"\x7fELF\x02\x01").unwrap();
+
+        #[cfg(unix)]
+        assert_eq!(
+            find_issues(&script),
+            vec![ScriptIssue::NotExecutable, ScriptIssue::NoShebang]
+        );
+        assert!(!find_issues(&binary).contains(&ScriptIssue::NoShebang));
+
+        for issue in find_issues(&script) {
+            fix_issue(&script, ScriptType::Python, issue).unwrap();
+        }
+        assert_eq!(find_issues(&script), vec![]);
+        assert_eq!(
+            std::fs::read_to_string(&script).unwrap(),
+            "#!/usr/bin/env python3\nprint('tool')\n"
+        );
+
+        // Without a known interpreter there is no #! line to add
+        assert!(fix_issue(&binary, ScriptType::Executable, ScriptIssue::NoShebang).is_err());
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
@@ -1,18 +1,18 @@
-use super::{confirm_script, fuzzy_get_script};
+use super::{
+    confirm_script, fuzzy_get_script, launcher::write_launcher, runs::logs_dir,
+    schedule::apply_schedules,
+};
 use crate::{
-    commands::groups::fuzzy_get_group,
+    commands::{collisions::check_script_name, groups::fuzzy_get_group, Context},
     error,
-    file_management::{
-        database::{db_conn, scripts::update_script},
-        update_runcom, Script,
-    },
+    file_management::{database::scripts::update_script, Script},
     success,
 };
 
 use console::style;
 
-fn get_script(db_file: &str, script_name: &str) -> Result<Script, &'static str> {
-    let script = match fuzzy_get_script(db_file, script_name) {
+pub(super) fn get_script(ctx: &Context, script_name: &str) -> Result<Script, &'static str> {
+    let script = match fuzzy_get_script(&ctx.conn, script_name) {
         Some(script) => script,
         None => return Err("Could not find script"),
     };
@@ -23,44 +23,35 @@ fn get_script(db_file: &str, script_name: &str) -> Result<Script, &'static str>
     Ok(script)
 }
 
-fn edit_script(
-    rc_file: &str,
-    db_file: &str,
-    old_script: &str,
-    new_script: &Script,
-    success_msg: String,
-) {
-    let conn = &db_conn(db_file);
-    let _ = match update_script(conn, old_script, &new_script) {
-        Ok(_) => true,
-        Err(_) => {
-            error!("Could not rename script");
-            return;
-        }
-    };
+fn edit_script(ctx: &Context, old_script: &str, new_script: &Script, success_msg: String) -> bool {
+    if let Err(err) =
+        ctx.commit(|tx| update_script(tx, old_script, new_script).map_err(String::from))
+    {
+        error!(format!("Could not update script: {}", err));
+        return false;
+    }
 
-    update_runcom(rc_file, db_file);
     success!(success_msg);
     println!(
         "Please run {} to activate changes",
         style("`exec \"$SHELL\"`").bold().italic()
     );
+    true
 }
 
-pub fn toggle_script(runcom_file: &str, db_file: &str, script_name: &str) {
-    let mut script = match get_script(db_file, script_name) {
+pub fn toggle_script(ctx: &Context, script_name: &str) {
+    let mut script = match get_script(ctx, script_name) {
         Ok(script) => script,
         Err(e) => {
-            error!(format!("{}", e));
+            error!(e.to_string());
             return;
         }
     };
 
     script.enabled = !script.enabled;
 
-    edit_script(
-        runcom_file,
-        db_file,
+    let toggled = edit_script(
+        ctx,
         &script.name,
         &script,
         format!(
@@ -72,18 +63,28 @@ pub fn toggle_script(runcom_file: &str, db_file: &str, script_name: &str) {
                 style("disabled").red()
             }
         ),
-    )
+    );
+    // Disabled scripts don't run on their schedule
+    if toggled && script.schedule.is_some() {
+        if let Err(err) = apply_schedules(ctx) {
+            error!(err);
+        }
+    }
 }
 
-pub fn rename_script(runcom_file: &str, db_file: &str, old_name: &str, new_name: &str) {
-    let mut script = match get_script(db_file, old_name) {
+pub fn rename_script(ctx: &Context, old_name: &str, new_name: &str, strict: bool) {
+    let mut script = match get_script(ctx, old_name) {
         Ok(script) => script,
         Err(e) => {
-            error!(format!("{}", e));
+            error!(e.to_string());
             return;
         }
     };
 
+    if !check_script_name(ctx, new_name, strict) {
+        return;
+    }
+
     // rename script
     // move script to new location reflecting new name
     // update script path in database
@@ -112,38 +113,38 @@ pub fn rename_script(runcom_file: &str, db_file: &str, old_name: &str, new_name:
         format!("{}.{}", new_name, script_ext)
     };
 
-    let new_script_path = std::path::Path::new(db_file)
+    let old_script_dir = std::path::Path::new(&script.path)
         .parent()
         .unwrap()
-        .join("scripts")
-        .join(new_name);
-
-    if let Err(err) = std::fs::create_dir(&new_script_path) {
-        error!(format!("Issue creating script directory: {}", err));
+        .to_path_buf();
+    let new_script_dir = ctx.scripts_dir().join(new_name);
+
+    if new_script_dir.exists() {
+        error!(format!(
+            "Script directory {} already exists",
+            new_script_dir.display()
+        ));
         return;
     }
 
-    if let Err(err) = std::fs::rename(&script.path, new_script_path.join(new_script_name)) {
-        error!(format!("Issue renaming script: {}", err));
+    // Move the whole directory so anything stored next to the script comes along
+    if let Err(err) = std::fs::rename(&old_script_dir, &new_script_dir) {
+        error!(format!("Issue moving script directory: {}", err));
         return;
     }
 
-    // remove old name dir
-    if let Err(err) = std::fs::remove_dir_all(std::path::Path::new(&script.path).parent().unwrap())
-    {
-        error!(format!("Issue removing old script directory: {}", err));
+    let moved_script_path = new_script_dir.join(script_file);
+    let new_script_path = new_script_dir.join(new_script_name);
+    if let Err(err) = std::fs::rename(&moved_script_path, &new_script_path) {
+        error!(format!("Issue renaming script: {}", err));
+        let _ = std::fs::rename(&new_script_dir, &old_script_dir);
         return;
     }
 
-    script.path = new_script_path
-        .join(script_file)
-        .to_str()
-        .unwrap()
-        .to_string();
+    script.path = new_script_path.to_str().unwrap().to_string();
 
-    edit_script(
-        runcom_file,
-        db_file,
+    let renamed = edit_script(
+        ctx,
         &old_name,
         &script,
         format!(
@@ -151,19 +152,43 @@ pub fn rename_script(runcom_file: &str, db_file: &str, old_name: &str, new_name:
             style(&old_name).bold().italic(),
             style(&script.name).bold().italic()
         ),
-    )
+    );
+
+    if !renamed {
+        // Database was rolled back, so put the files back where it expects them
+        let _ = std::fs::rename(&new_script_path, &moved_script_path);
+        let _ = std::fs::rename(&new_script_dir, &old_script_dir);
+    } else {
+        // The venv and logs are named after the script and launchers hold paths that just moved
+        let old_venv = ctx.venvs_dir().join(&old_name);
+        if old_venv.exists() {
+            let _ = std::fs::rename(&old_venv, ctx.venvs_dir().join(&script.name));
+        }
+        let old_logs = logs_dir(ctx, &old_name);
+        if old_logs.exists() {
+            let _ = std::fs::rename(&old_logs, logs_dir(ctx, &script.name));
+        }
+        if let Err(err) = write_launcher(ctx, &script) {
+            error!(err);
+        }
+        if script.schedule.is_some() {
+            if let Err(err) = apply_schedules(ctx) {
+                error!(err);
+            }
+        }
+    }
 }
 
-pub fn move_script(runcom_file: &str, db_file: &str, script_name: &str, group_name: &str) {
-    let mut script = match get_script(db_file, script_name) {
+pub fn move_script(ctx: &Context, script_name: &str, group_name: &str) {
+    let mut script = match get_script(ctx, script_name) {
         Ok(script) => script,
         Err(e) => {
-            error!(format!("{}", e));
+            error!(e.to_string());
             return;
         }
     };
 
-    let group = match fuzzy_get_group(db_file, group_name) {
+    let group = match fuzzy_get_group(&ctx.conn, group_name) {
         Some(group) => group,
         None => {
             error!("Group not found");
@@ -180,8 +205,7 @@ pub fn move_script(runcom_file: &str, db_file: &str, script_name: &str, group_na
     script.group_id = group.id;
 
     edit_script(
-        runcom_file,
-        db_file,
+        ctx,
         &script.name,
         &script,
         format!(
@@ -189,13 +213,16 @@ pub fn move_script(runcom_file: &str, db_file: &str, script_name: &str, group_na
             style(&script.name).italic().bold(),
             style(&group.name).bold().underlined()
         ),
-    )
+    );
 }
 
 #[cfg(test)]
 mod tests {
     use crate::{
-        commands::{groups::add::add_group, scripts::add::add_script},
+        commands::{
+            groups::add::add_group,
+            scripts::{add::add_script, check::FixIssues},
+        },
         file_management::database::scripts::get_script_by_name,
     };
 
@@ -210,7 +237,7 @@ mod tests {
         // create test folder
         assert!(std::fs::create_dir(test_dir).is_ok());
 
-        let conn = db_conn(db_file);
+        let ctx = Context::new(rc_file, db_file).unwrap();
         assert!(std::path::Path::new(db_file).exists());
 
         // Create test script
@@ -218,9 +245,9 @@ mod tests {
         let script = r#"echo "test script""#;
         std::fs::write(script_path, script).expect("Error creating test script");
 
-        add_script(rc_file, db_file, script_path, "", 1);
+        add_script(&ctx, script_path, "", None, false, false, FixIssues::Never);
 
-        let script = get_script_by_name(&conn, "edit_script_test_script");
+        let script = get_script_by_name(&ctx.conn, "edit_script_test_script");
         assert!(script.is_ok());
         assert_eq!(script.clone().unwrap().name, "edit_script_test_script");
         assert_eq!(
@@ -229,27 +256,27 @@ mod tests {
         );
 
         // Test toggle script
-        toggle_script(rc_file, db_file, "edit_script_test_script");
-        let script = get_script_by_name(&conn, "edit_script_test_script");
+        toggle_script(&ctx, "edit_script_test_script");
+        let script = get_script_by_name(&ctx.conn, "edit_script_test_script");
         assert!(script.is_ok());
         assert_eq!(script.clone().unwrap().name, "edit_script_test_script");
         assert!(!script.clone().unwrap().enabled);
 
         // Test move script
-        add_group(db_file, "Group1");
-        move_script(rc_file, db_file, "edit_script_test_script", "Group1");
-        let script = get_script_by_name(&conn, "edit_script_test_script");
+        add_group(&ctx, "Group1");
+        move_script(&ctx, "edit_script_test_script", "Group1");
+        let script = get_script_by_name(&ctx.conn, "edit_script_test_script");
         assert!(script.is_ok());
         assert_eq!(script.clone().unwrap().name, "edit_script_test_script");
 
         // Test rename script
         rename_script(
-            rc_file,
-            db_file,
+            &ctx,
             "edit_script_test_script",
             "new_edit_script_test_script",
+            false,
         );
-        let script = get_script_by_name(&conn, "new_edit_script_test_script");
+        let script = get_script_by_name(&ctx.conn, "new_edit_script_test_script");
         assert!(script.is_ok());
         assert_eq!(script.clone().unwrap().name, "new_edit_script_test_script");
 
@@ -0,0 +1,277 @@
+use std::path::Path;
+
+use console::style;
+
+use crate::{
+    commands::{collisions::find_executable, Context},
+    file_management::database::{groups::get_group_by_name, scripts::get_script_by_name},
+    warning,
+};
+
+/// Metadata documented at the top of a script, in a block of `# Key: value` comments or a
+/// Python docstring
+#[derive(Debug, Default, PartialEq)]
+pub struct ScriptHeader {
+    pub description: Option<String>,
+    pub usage: Option<String>,
+    /// Name of the group the script belongs in
+    pub group: Option<String>,
+    /// Commands the script needs on `$PATH`
+    pub requires: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Field {
+    Description,
+    Usage,
+    Group,
+    Requires,
+}
+
+/// The field a `Key: value` line sets and its value
+fn parse_field(line: &str) -> Option<(Field, &str)> {
+    let (key, value) = line.split_once(':')?;
+    let field = match key.to_ascii_lowercase().as_str() {
+        "description" => Field::Description,
+        "usage" => Field::Usage,
+        "group" => Field::Group,
+        "requires" => Field::Requires,
+        _ => return None,
+    };
+    Some((field, value.trim()))
+}
+
+fn split_requires(value: &str) -> impl Iterator<Item = String> + '_ {
+    value
+        .split([',', ' '])
+        .filter(|command| !command.is_empty())
+        .map(String::from)
+}
+
+impl ScriptHeader {
+    fn set(&mut self, field: Field, value: &str) {
+        match field {
+            Field::Description => self.description = Some(value.to_string()),
+            Field::Usage => self.usage = Some(value.to_string()),
+            Field::Group => self.group = Some(value.to_string()),
+            Field::Requires => self.requires.extend(split_requires(value)),
+        }
+    }
+
+    /// Add an indented line following `field`, descriptions are joined into one line while each
+    /// line of a usage is kept
+    fn extend(&mut self, field: Field, line: &str) {
+        let (text, separator) = match field {
+            Field::Description => (&mut self.description, ' '),
+            Field::Usage => (&mut self.usage, '\n'),
+            Field::Group => return,
+            Field::Requires => {
+                self.requires.extend(split_requires(line));
+                return;
+            }
+        };
+        let text = text.get_or_insert_with(String::new);
+        if !text.is_empty() {
+            text.push(separator);
+        }
+        text.push_str(line);
+    }
+
+    /// Read the fields from the lines of a header, with comment markers already removed
+    ///
+    /// A field carries on over the indented lines after it and ends at a blank line.
+    fn from_lines<'a>(lines: impl IntoIterator<Item = &'a str>) -> ScriptHeader {
+        let mut header = ScriptHeader::default();
+        let mut current = None;
+
+        for line in lines {
+            if line.trim().is_empty() {
+                current = None;
+            } else if line.starts_with(char::is_whitespace) {
+                if let Some(field) = current {
+                    header.extend(field, line.trim());
+                }
+            } else if let Some((field, value)) = parse_field(line) {
+                header.set(field, value);
+                current = Some(field);
+            } else {
+                current = None;
+            }
+        }
+
+        header.description = header.description.filter(|text| !text.is_empty());
+        header.usage = header.usage.filter(|text| !text.is_empty());
+        header.group = header.group.filter(|text| !text.is_empty());
+        header
+    }
+}
+
+/// Text of a comment line, `None` if `line` isn't one
+fn comment_text(line: &str) -> Option<&str> {
+    let line = line.trim_start();
+    let text = line.strip_prefix("//").or_else(|| line.strip_prefix('#'))?;
+    // Keep any indentation past the space after the marker so continuations can be told apart
+    Some(text.strip_prefix(' ').unwrap_or(text))
+}
+
+/// Lines of the docstring starting at the first of `lines`, `None` if there isn't one
+fn docstring<'a>(lines: &[&'a str]) -> Option<Vec<&'a str>> {
+    let first = lines.first()?.trim_start();
+    let quote = ["\"\"\"", "'''"]
+        .into_iter()
+        .find(|quote| first.starts_with(quote))?;
+
+    let first = &first[quote.len()..];
+    if let Some(end) = first.find(quote) {
+        return Some(vec![&first[..end]]);
+    }
+    let mut docstring = vec![first];
+    for line in &lines[1..] {
+        match line.find(quote) {
+            Some(end) => {
+                docstring.push(&line[..end]);
+                return Some(docstring);
+            }
+            None => docstring.push(line),
+        }
+    }
+    // Never closed, not a docstring
+    None
+}
+
+/// Parse the header of a script's `content`, after any `#!` line
+///
+/// A Python docstring is used if the script starts with one, its first paragraph is the
+/// description unless a `Description:` field is given.
+pub fn parse_header(content: &str) -> ScriptHeader {
+    let lines: Vec<&str> = content
+        .lines()
+        .skip_while(|line| line.starts_with("#!"))
+        .skip_while(|line| line.trim().is_empty())
+        .collect();
+
+    // Coding and linter comments can come before a docstring
+    let code_start = lines
+        .iter()
+        .position(|line| comment_text(line).is_none())
+        .unwrap_or(lines.len());
+    if let Some(docstring) = docstring(&lines[code_start..]) {
+        let mut header = ScriptHeader::from_lines(docstring.iter().copied());
+        if header.description.is_none() {
+            let summary = docstring
+                .iter()
+                .map(|line| line.trim())
+                .skip_while(|line| line.is_empty())
+                .take_while(|line| !line.is_empty())
+                .collect::<Vec<_>>();
+            if summary
+                .first()
+                .is_some_and(|line| parse_field(line).is_none())
+            {
+                header.description = Some(summary.join(" "));
+            }
+        }
+        return header;
+    }
+
+    ScriptHeader::from_lines(lines.iter().map_while(|line| comment_text(line)))
+}
+
+/// Header of the script at `path`, empty if it can't be read as text
+pub fn read_header(path: &Path) -> ScriptHeader {
+    std::fs::read_to_string(path)
+        .map(|content| parse_header(&content))
+        .unwrap_or_default()
+}
+
+/// Id of the group named in `header`, `None` if it doesn't name one or the group doesn't exist
+pub fn header_group_id(ctx: &Context, header: &ScriptHeader) -> Option<i32> {
+    let name = header.group.as_deref()?;
+    match get_group_by_name(&ctx.conn, name) {
+        Ok(group) => Some(group.id),
+        Err(_) => {
+            warning!(format!(
+                "Group {} from the script's header does not exist",
+                style(name).bold()
+            ));
+            None
+        }
+    }
+}
+
+/// Warn about commands in the `Requires:` field of `header` that can't be found
+pub fn check_requires(ctx: &Context, header: &ScriptHeader) {
+    let path_var = std::env::var("PATH").unwrap_or_default();
+    for command in &header.requires {
+        let found = find_executable(command, &path_var, ctx.nym_dir()).is_some()
+            || get_script_by_name(&ctx.conn, command).is_ok();
+        if !found {
+            warning!(format!(
+                "Script requires {}, which is not on $PATH",
+                style(command).bold()
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_header_test() {
+        let header = parse_header(
+            "#!/bin/bash\n\
+            \n\
+            # Description: Back up a directory\n\
+            #   to the NAS\n\
+            # Usage: backup <dir>\n\
+            #        backup --all\n\
+            # Group: maintenance\n\
+            # Requires: rsync, ssh\n\
+            #\n\
+            # shellcheck disable=SC2086\n\
+            \n\
+            # Usage: not part of the header\n\
+            rsync \"$1\" nas:\n",
+        );
+        assert_eq!(
+            header,
+            ScriptHeader {
+                description: Some("Back up a directory to the NAS".to_string()),
+                usage: Some("backup <dir>\nbackup --all".to_string()),
+                group: Some("maintenance".to_string()),
+                requires: vec!["rsync".to_string(), "ssh".to_string()],
+            }
+        );
+
+        let header = parse_header(
+            "#!/usr/bin/env python3\n\
+            # -*- coding: utf-8 -*-\n\
+            \"\"\"Resize images\n\
+            for the blog.\n\
+            \n\
+            Usage:\n\
+            \x20   resize.py <image>...\n\
+            \x20   resize.py --all\n\
+            Requires: convert\n\
+            \"\"\"\n\
+            import sys\n",
+        );
+        assert_eq!(
+            header,
+            ScriptHeader {
+                description: Some("Resize images for the blog.".to_string()),
+                usage: Some("resize.py <image>...\nresize.py --all".to_string()),
+                group: None,
+                requires: vec!["convert".to_string()],
+            }
+        );
+
+        assert_eq!(
+            parse_header("'''Print the date'''\nprint(1)\n").description,
+            Some("Print the date".to_string())
+        );
+        assert_eq!(parse_header("echo no header\n"), ScriptHeader::default());
+    }
+}
@@ -0,0 +1,402 @@
+use std::path::{Path, PathBuf};
+
+use console::style;
+use rusqlite::Connection;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    commands::Context,
+    error,
+    file_management::{
+        database::script_versions::{add_script_version, get_script_versions},
+        Script, ScriptVersion,
+    },
+    helpers::{diff::print_unified_diff, questions::yesno},
+    info, success, warning,
+};
+
+use super::{check::FixIssues, edit::get_script, update::replace_script};
+
+/// Directory the saved versions of a script are kept in, next to the script itself
+pub fn versions_dir(script: &Script) -> PathBuf {
+    Path::new(&script.path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join(".versions")
+}
+
+/// Where the copy of `version` is stored
+pub fn version_path(script: &Script, version: &ScriptVersion) -> PathBuf {
+    versions_dir(script).join(format!("{}-{}", version.version, version.file_name))
+}
+
+/// Hex encoded SHA-256 of a file's contents
+pub fn hash_content(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Save the file at `content_path` as the next version of `script`
+///
+/// The copy is named after `script`'s file, so pass the script as it will be once the file is in
+/// place.
+pub fn record_version(
+    conn: &Connection,
+    script: &Script,
+    content_path: &Path,
+    message: &str,
+) -> Result<ScriptVersion, String> {
+    let content = std::fs::read(content_path)
+        .map_err(|err| format!("Could not read {}: {}", content_path.display(), err))?;
+    let last_version = get_script_versions(conn, &script.name)
+        .last()
+        .map_or(0, |version| version.version);
+
+    let version = ScriptVersion {
+        version: last_version + 1,
+        created_at: chrono::Utc::now().timestamp(),
+        hash: hash_content(&content),
+        message: message.to_string(),
+        file_name: Path::new(&script.path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(&script.name)
+            .to_string(),
+    };
+
+    std::fs::create_dir_all(versions_dir(script))
+        .map_err(|err| format!("Could not create versions directory: {}", err))?;
+    std::fs::write(version_path(script, &version), &content)
+        .map_err(|err| format!("Could not save script version: {}", err))?;
+    add_script_version(conn, &script.name, &version)?;
+    Ok(version)
+}
+
+pub(super) fn format_timestamp(timestamp: i64) -> String {
+    match chrono::DateTime::from_timestamp(timestamp, 0) {
+        Some(time) => time
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d %H:%M")
+            .to_string(),
+        None => timestamp.to_string(),
+    }
+}
+
+/// Hash of the script's file as it is now, `None` if it can't be read
+pub(super) fn current_hash(script: &Script) -> Option<String> {
+    std::fs::read(&script.path)
+        .ok()
+        .map(|content| hash_content(&content))
+}
+
+/// List the saved versions of a script, marking the one matching the current file
+pub fn script_history(ctx: &Context, name: &str) {
+    let script = match get_script(ctx, name) {
+        Ok(script) => script,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let versions = get_script_versions(&ctx.conn, &script.name);
+    if versions.is_empty() {
+        warning!(format!(
+            "No versions of {} have been saved",
+            style(&script.name).bold()
+        ));
+        return;
+    }
+
+    let current = current_hash(&script);
+    let current_version = versions
+        .iter()
+        .rev()
+        .find(|version| Some(&version.hash) == current.as_ref())
+        .map(|version| version.version);
+
+    for version in &versions {
+        println!(
+            "{:>4}  {}  {}  {}{}",
+            style(version.version).bold(),
+            format_timestamp(version.created_at),
+            style(&version.hash[..8]).dim(),
+            version.message,
+            if current_version == Some(version.version) {
+                style(" (current)").green().to_string()
+            } else {
+                "".to_string()
+            }
+        );
+    }
+    if current_version.is_none() {
+        warning!("The script has been changed since its last saved version");
+    }
+}
+
+fn find_version(versions: &[ScriptVersion], number: i32) -> Result<&ScriptVersion, String> {
+    versions
+        .iter()
+        .find(|version| version.version == number)
+        .ok_or(format!("Version {} does not exist", number))
+}
+
+/// Show a unified diff between two versions of a script
+///
+/// `new` defaults to the current file. `old` defaults to the version before the current one, or
+/// the last saved version if the file has been changed since.
+pub fn script_diff(ctx: &Context, name: &str, old: Option<i32>, new: Option<i32>) {
+    let script = match get_script(ctx, name) {
+        Ok(script) => script,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+    let versions = get_script_versions(&ctx.conn, &script.name);
+
+    let old = match old {
+        Some(number) => find_version(&versions, number),
+        None => {
+            let saved = match versions.last() {
+                Some(version) if Some(&version.hash) == current_hash(&script).as_ref() => {
+                    versions.iter().rev().nth(1)
+                }
+                last => last,
+            };
+            saved.ok_or("There is no earlier version to compare with".to_string())
+        }
+    };
+    let old = match old {
+        Ok(version) => version,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let (new_path, new_label) = match new.map(|number| find_version(&versions, number)) {
+        Some(Ok(version)) => (
+            version_path(&script, version),
+            format!("version {}", version.version),
+        ),
+        Some(Err(err)) => {
+            error!(err);
+            return;
+        }
+        None => (PathBuf::from(&script.path), "current".to_string()),
+    };
+
+    let old_content = std::fs::read(version_path(&script, old)).unwrap_or_default();
+    let new_content = match std::fs::read(&new_path) {
+        Ok(content) => content,
+        Err(err) => {
+            error!(format!("Could not read {}: {}", new_path.display(), err));
+            return;
+        }
+    };
+
+    if old_content == new_content {
+        info!(format!(
+            "version {} and {} are the same",
+            old.version, new_label
+        ));
+        return;
+    }
+    print_unified_diff(
+        &String::from_utf8_lossy(&old_content),
+        &String::from_utf8_lossy(&new_content),
+        &format!("version {}", old.version),
+        &new_label,
+    );
+}
+
+/// Restore a saved version of a script, the restored file is saved as a new version
+pub fn rollback_script(ctx: &Context, name: &str, number: i32, yes: bool) {
+    let script = match get_script(ctx, name) {
+        Ok(script) => script,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+    if script.entry_point.is_some() {
+        error!(format!(
+            "Project {} doesn't keep versions",
+            style(&script.name).bold()
+        ));
+        return;
+    }
+    if script.linked {
+        error!(format!(
+            "Script {} is a link to {}, restore that file instead",
+            style(&script.name).bold(),
+            script.source_path.as_deref().unwrap_or_default()
+        ));
+        return;
+    }
+    let versions = get_script_versions(&ctx.conn, &script.name);
+    let version = match find_version(&versions, number) {
+        Ok(version) => version,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let saved_path = version_path(&script, version);
+    let saved_content = match std::fs::read(&saved_path) {
+        Ok(content) => content,
+        Err(err) => {
+            error!(format!("Could not read {}: {}", saved_path.display(), err));
+            return;
+        }
+    };
+    let current_content = std::fs::read(&script.path).unwrap_or_default();
+
+    // The script may have been renamed since, so only the version's extension is restored
+    let current_path = Path::new(&script.path);
+    let new_path = current_path.with_extension(
+        Path::new(&version.file_name)
+            .extension()
+            .unwrap_or_default(),
+    );
+
+    if saved_content == current_content && new_path == current_path {
+        info!(format!(
+            "Script {} is already at version {}",
+            style(&script.name).bold(),
+            number
+        ));
+        return;
+    }
+
+    print_unified_diff(
+        &String::from_utf8_lossy(&current_content),
+        &String::from_utf8_lossy(&saved_content),
+        "current",
+        &format!("version {}", number),
+    );
+    if new_path != current_path {
+        info!(format!("Script will be stored as {}", new_path.display()));
+    }
+
+    if !yes && !yesno!(format!("Roll back to version {}?", number)).unwrap_or(false) {
+        println!("{}", style("Aborting").yellow());
+        return;
+    }
+
+    match replace_script(
+        ctx,
+        &script,
+        &saved_path,
+        &new_path,
+        // The source itself hasn't changed
+        None,
+        &format!("Rolled back to version {}", number),
+        FixIssues::Never,
+    ) {
+        Ok(()) => success!(format!(
+            "Script {} rolled back to version {}",
+            style(&script.name).bold(),
+            number
+        )),
+        Err(err) => error!(format!("Could not roll back script: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::scripts::{add::add_script, edit::rename_script, update::update_script},
+        file_management::database::scripts::get_script_by_name,
+    };
+
+    use super::*;
+
+    #[test]
+    fn script_history_test() {
+        let test_dir = "script_history_test";
+        let db_file = "script_history_test/nym.db";
+        let rc_file = "script_history_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        let source = "script_history_test/history_script.sh";
+        std::fs::write(source, "echo one\n").unwrap();
+        add_script(&ctx, source, "", None, false, false, FixIssues::Never);
+
+        std::fs::write(source, "echo two\n").unwrap();
+        update_script(
+            &ctx,
+            "history_script",
+            None,
+            Some("second"),
+            true,
+            FixIssues::Never,
+        );
+        let python = "script_history_test/history_script.py";
+        std::fs::write(python, "print('three')\n").unwrap();
+        update_script(
+            &ctx,
+            "history_script",
+            Some(python),
+            None,
+            true,
+            FixIssues::Never,
+        );
+
+        let versions = get_script_versions(&ctx.conn, "history_script");
+        assert_eq!(
+            versions.iter().map(|v| v.version).collect::<Vec<_>>(),
+            vec![1, 2, 3]
+        );
+        assert_eq!(versions[1].message, "second");
+        assert_eq!(versions[0].hash, hash_content(b"echo one\n"));
+        assert_eq!(versions[2].file_name, "history_script.py");
+
+        let script = get_script_by_name(&ctx.conn, "history_script").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(version_path(&script, &versions[0])).unwrap(),
+            "echo one\n"
+        );
+
+        // Updating from a file makes it the source, a rollback leaves the source alone
+        assert_eq!(
+            script.source_hash.as_deref(),
+            Some(hash_content(b"print('three')\n").as_str())
+        );
+        rollback_script(&ctx, "history_script", 1, true);
+        let rolled_back = get_script_by_name(&ctx.conn, "history_script").unwrap();
+        assert_eq!(rolled_back.source_hash, script.source_hash);
+        let script = rolled_back;
+
+        // Rolling back restores the old extension and content as a new version
+        assert!(script.path.ends_with("history_script/history_script.sh"));
+        assert_eq!(std::fs::read_to_string(&script.path).unwrap(), "echo one\n");
+        let versions = get_script_versions(&ctx.conn, "history_script");
+        assert_eq!(versions.len(), 4);
+        assert_eq!(versions[3].message, "Rolled back to version 1");
+        assert_eq!(versions[3].hash, versions[0].hash);
+
+        // Unknown versions leave the script alone
+        rollback_script(&ctx, "history_script", 9, true);
+        assert_eq!(get_script_versions(&ctx.conn, "history_script").len(), 4);
+
+        // A renamed script keeps its new name when rolled back
+        rename_script(&ctx, "history_script", "renamed_script", false);
+        rollback_script(&ctx, "renamed_script", 3, true);
+        let script = get_script_by_name(&ctx.conn, "renamed_script").unwrap();
+        assert!(script
+            .path
+            .ends_with("scripts/renamed_script/renamed_script.py"));
+        assert_eq!(
+            std::fs::read_to_string(&script.path).unwrap(),
+            "print('three')\n"
+        );
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
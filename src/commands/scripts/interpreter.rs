@@ -0,0 +1,157 @@
+use std::{
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use crate::file_management::ScriptType;
+
+/// Type named by the program of a `#!` line, e.g. `#!/usr/bin/env python3`
+fn shebang_type(line: &str) -> Option<ScriptType> {
+    let mut words = line.strip_prefix("#!")?.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        // Skip options such as `env -S`
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+
+    // Versioned names, e.g. python3.12 or ruby3
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    match name {
+        "python" => Some(ScriptType::Python),
+        "node" | "nodejs" => Some(ScriptType::Node),
+        "ruby" => Some(ScriptType::Ruby),
+        "bash" | "sh" => Some(ScriptType::Bash),
+        _ => None,
+    }
+}
+
+fn extension_type(path: &Path) -> ScriptType {
+    match path.extension().and_then(|ext| ext.to_str()) {
+        Some("py") => ScriptType::Python,
+        Some("js" | "mjs" | "cjs") => ScriptType::Node,
+        Some("rb") => ScriptType::Ruby,
+        Some("sh" | "bash") => ScriptType::Bash,
+        _ => ScriptType::Executable,
+    }
+}
+
+/// Language of the script at `path`, from its shebang or failing that its extension
+///
+/// A script whose shebang names a program nym doesn't know, e.g. zsh, is run directly so the
+/// shebang picks its interpreter.
+pub fn detect_script_type(path: &Path) -> ScriptType {
+    let first_line = std::fs::File::open(path).ok().and_then(|file| {
+        let mut line = String::new();
+        BufReader::new(file).read_line(&mut line).ok()?;
+        Some(line)
+    });
+
+    match first_line {
+        Some(line) if line.starts_with("#!") => {
+            shebang_type(&line).unwrap_or(ScriptType::Executable)
+        }
+        _ => extension_type(path),
+    }
+}
+
+/// `requirements.txt` in `dir`, the directory a Python script comes from
+pub fn find_requirements(dir: &Path) -> Option<PathBuf> {
+    Some(dir.join("requirements.txt")).filter(|path| path.is_file())
+}
+
+/// Python executable inside a venv
+pub fn venv_python(venv: &Path) -> PathBuf {
+    if cfg!(windows) {
+        venv.join("Scripts").join("python.exe")
+    } else {
+        venv.join("bin").join("python")
+    }
+}
+
+/// First of python3 and python that runs
+fn find_python() -> Option<&'static str> {
+    ["python3", "python"].into_iter().find(|python| {
+        Command::new(python)
+            .arg("--version")
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .status()
+            .is_ok_and(|status| status.success())
+    })
+}
+
+/// Create a venv at `venv` with the local python, or reuse the one there, and install
+/// `requirements` into it
+pub fn create_venv(venv: &Path, requirements: &Path) -> Result<(), String> {
+    if !venv_python(venv).exists() {
+        let python = find_python().ok_or("Could not find python3 to create a venv with")?;
+        let status = Command::new(python)
+            .args(["-m", "venv"])
+            .arg(venv)
+            .status()
+            .map_err(|err| format!("Could not run {}: {}", python, err))?;
+        if !status.success() {
+            return Err(format!("Could not create venv at {}", venv.display()));
+        }
+    }
+
+    let status = Command::new(venv_python(venv))
+        .args(["-m", "pip", "install", "--quiet", "-r"])
+        .arg(requirements)
+        .status()
+        .map_err(|err| format!("Could not run pip: {}", err))?;
+    if !status.success() {
+        return Err(format!(
+            "Could not install the requirements in {}",
+            requirements.display()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_script_type_test() {
+        assert_eq!(
+            shebang_type("#!/usr/bin/env python3\n"),
+            Some(ScriptType::Python)
+        );
+        assert_eq!(
+            shebang_type("#!/usr/bin/env -S node --no-warnings"),
+            Some(ScriptType::Node)
+        );
+        assert_eq!(
+            shebang_type("#!/usr/local/bin/python3.12"),
+            Some(ScriptType::Python)
+        );
+        assert_eq!(shebang_type("#!/bin/sh"), Some(ScriptType::Bash));
+        assert_eq!(shebang_type("#!/bin/zsh"), None);
+        assert_eq!(shebang_type("#!/usr/bin/env dash"), None);
+        assert_eq!(shebang_type("#!/usr/bin/perl"), None);
+        assert_eq!(shebang_type("import os"), None);
+
+        let test_dir = Path::new("detect_script_type_test");
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ruby = test_dir.join("tool.py");
+        std::fs::write(&ruby, "#!/usr/bin/env ruby\nputs 1\n").unwrap();
+        let plain = test_dir.join("tool");
+        std::fs::write(&plain, "import sys\n").unwrap();
+        let node = test_dir.join("tool.mjs");
+        std::fs::write(&node, "console.log(1)\n").unwrap();
+        let zsh = test_dir.join("tool.sh");
+        std::fs::write(&zsh, "#!/bin/zsh\nprint -P '%F{red}1'\n").unwrap();
+
+        // The shebang wins over the extension
+        assert_eq!(detect_script_type(&ruby), ScriptType::Ruby);
+        assert_eq!(detect_script_type(&plain), ScriptType::Executable);
+        assert_eq!(detect_script_type(&node), ScriptType::Node);
+        // Not run with bash just because of its extension
+        assert_eq!(detect_script_type(&zsh), ScriptType::Executable);
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
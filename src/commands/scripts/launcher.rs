@@ -0,0 +1,175 @@
+use std::path::{Path, PathBuf};
+
+use crate::{
+    commands::Context,
+    file_management::{Script, ScriptType},
+    helpers::shell::Shell,
+    info,
+};
+
+use super::{
+    interpreter::{create_venv, find_requirements, venv_python},
+    project::project_dir,
+};
+
+/// Directory holding the launcher of a single file script, put on PATH instead of the script's
+/// own directory when it exists
+pub fn launcher_dir(script: &Script) -> PathBuf {
+    Path::new(&script.path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join("bin")
+}
+
+/// Venv of a Python script, `None` if it hasn't got one
+pub fn script_venv(ctx: &Context, script: &Script) -> Option<PathBuf> {
+    let venv = ctx.venvs_dir().join(&script.name);
+    (script.type_ == ScriptType::Python && venv_python(&venv).exists()).then_some(venv)
+}
+
+/// Shell script that runs `target` with `interpreter`, or directly if there isn't one, with
+/// `NYM_PROJECT_DIR` set to `project`
+///
+/// The working directory is the caller's, so relative paths given as arguments still work.
+fn launcher(project: Option<&Path>, interpreter: Option<&str>, target: &str) -> String {
+    let posix = Shell::Bash;
+    let mut launcher = format!("#!/bin/sh\n# Generated by nym, runs {}\n", target);
+    if let Some(project) = project {
+        launcher.push_str(&format!(
+            "NYM_PROJECT_DIR={}\nexport NYM_PROJECT_DIR\n",
+            posix.quote(&project.to_string_lossy())
+        ));
+    }
+    launcher.push_str("exec ");
+    if let Some(interpreter) = interpreter {
+        launcher.push_str(&posix.quote(interpreter));
+        launcher.push(' ');
+    }
+    launcher.push_str(&posix.quote(target));
+    launcher.push_str(" \"$@\"\n");
+    launcher
+}
+
+fn write_executable(path: &Path, content: &str) -> Result<(), String> {
+    std::fs::write(path, content).map_err(|err| format!("Could not write launcher: {}", err))?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))
+            .map_err(|err| format!("Could not make launcher executable: {}", err))?;
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+/// Write the launcher of a script if it needs one
+///
+/// Projects always run through a launcher at the script's path, which tells the entry point
+/// where the project is in `NYM_PROJECT_DIR`. A single file script only gets one, in [`launcher_dir`], to run it in its venv.
+/// Launchers hold absolute paths, so they have to be rewritten whenever the script moves.
+pub fn write_launcher(ctx: &Context, script: &Script) -> Result<(), String> {
+    let venv_python = script_venv(ctx, script).map(|venv| venv_python(&venv));
+    let venv_python = venv_python.as_deref().map(|path| path.to_string_lossy());
+
+    if let Some(entry_point) = &script.entry_point {
+        let project = std::path::absolute(project_dir(script))
+            .map_err(|err| format!("Could not find project directory: {}", err))?;
+        // Without a venv an entry point that isn't executable is run by its interpreter
+        let interpreter = match venv_python.as_deref() {
+            Some(python) => Some(python),
+            None if !is_executable(&project.join(entry_point)) => script.type_.interpreter(),
+            None => None,
+        };
+        return write_executable(
+            Path::new(&script.path),
+            &launcher(
+                Some(&project),
+                interpreter,
+                &project.join(entry_point).to_string_lossy(),
+            ),
+        );
+    }
+
+    let bin_dir = launcher_dir(script);
+    let _ = std::fs::remove_dir_all(&bin_dir);
+    let python = match venv_python.as_deref() {
+        Some(python) => python,
+        None => return Ok(()),
+    };
+
+    let script_path = std::path::absolute(&script.path)
+        .map_err(|err| format!("Could not find script: {}", err))?;
+    std::fs::create_dir_all(&bin_dir)
+        .map_err(|err| format!("Could not create launcher directory: {}", err))?;
+    write_executable(
+        &bin_dir.join(script_path.file_name().unwrap_or_default()),
+        &launcher(None, Some(python), &script_path.to_string_lossy()),
+    )
+}
+
+/// Create or refresh the venv of a Python script from the `requirements.txt` in
+/// `requirements_dir`, if there is one
+pub fn install_requirements(
+    ctx: &Context,
+    script: &Script,
+    requirements_dir: Option<&Path>,
+) -> Result<(), String> {
+    let requirements = requirements_dir.and_then(find_requirements);
+    if let (ScriptType::Python, Some(requirements)) = (script.type_, requirements) {
+        info!(format!(
+            "Installing {} into the venv of {}",
+            requirements.display(),
+            script.name
+        ));
+        create_venv(&ctx.venvs_dir().join(&script.name), &requirements)?;
+    }
+    Ok(())
+}
+
+/// Set up the venv of a newly stored script and write its launcher
+pub fn prepare_script(
+    ctx: &Context,
+    script: &Script,
+    requirements_dir: Option<&Path>,
+) -> Result<(), String> {
+    install_requirements(ctx, script, requirements_dir)?;
+    write_launcher(ctx, script)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn launcher_test() {
+        assert_eq!(
+            launcher(
+                Some(Path::new("/home/me/.nym/scripts/tool/project")),
+                None,
+                "/home/me/.nym/scripts/tool/project/bin/it's.py"
+            ),
+            "#!/bin/sh\n# Generated by nym, runs /home/me/.nym/scripts/tool/project/bin/it's.py\n\
+            NYM_PROJECT_DIR='/home/me/.nym/scripts/tool/project'\nexport NYM_PROJECT_DIR\n\
+            exec '/home/me/.nym/scripts/tool/project/bin/it'\\''s.py' \"$@\"\n"
+        );
+        assert_eq!(
+            launcher(
+                None,
+                Some("/home/me/.nym/venvs/tool/bin/python"),
+                "/home/me/.nym/scripts/tool/tool.py"
+            ),
+            "#!/bin/sh\n# Generated by nym, runs /home/me/.nym/scripts/tool/tool.py\n\
+            exec '/home/me/.nym/venvs/tool/bin/python' '/home/me/.nym/scripts/tool/tool.py' \"$@\"\n"
+        );
+    }
+}
@@ -1,14 +1,46 @@
+use console::style;
+
 use crate::{
-    file_management::database::{db_conn, scripts::get_all_scripts},
+    commands::Context,
+    file_management::{database::scripts::get_all_scripts, Script},
     warning,
 };
 
-use super::fuzzy_get_script;
+use super::{
+    check::{check_script, describe_issue},
+    fuzzy_get_script,
+};
 
-pub fn list_scripts(db_file: &str) {
-    let conn = db_conn(db_file);
+/// Where a linked script points, anything stopping it from running and the issues found when it
+/// was added, to append to its entry
+pub fn script_notes(script: &Script) -> String {
+    let mut notes = String::new();
+    if script.linked {
+        notes.push_str(&format!(
+            " → {}",
+            script.source_path.as_deref().unwrap_or_default()
+        ));
+    }
+    if let Some(schedule) = &script.schedule {
+        notes.push_str(&format!(" {}", style(format!("(runs {})", schedule)).dim()));
+    }
+    for problem in check_script(script) {
+        notes.push_str(&format!(
+            " {}",
+            style(format!("({})", problem.describe())).red()
+        ));
+    }
+    for issue in &script.issues {
+        notes.push_str(&format!(
+            " {}",
+            style(format!("({})", describe_issue(*issue))).red()
+        ));
+    }
+    notes
+}
 
-    let scripts = get_all_scripts(&conn);
+pub fn list_scripts(ctx: &Context) {
+    let scripts = get_all_scripts(&ctx.conn);
 
     if scripts.is_empty() {
         warning!("No scripts found");
@@ -24,21 +56,27 @@ pub fn list_scripts(db_file: &str) {
             .unwrap();
 
         if script.enabled {
-            println!("✅ {}", script_file);
+            println!("✅ {}{}", script_file, script_notes(&script));
         } else {
-            println!("❌ {}", script_file);
+            println!("❌ {}{}", script_file, script_notes(&script));
         }
     }
 }
 
-pub fn script_manual(db_file: &str, name: &str) {
-    let script = fuzzy_get_script(db_file, name);
+pub fn script_manual(ctx: &Context, name: &str) {
+    let script = fuzzy_get_script(&ctx.conn, name);
     match script {
         Some(script) => {
             if script.name != name {
                 println!("Script {} not found showing {}", name, script.name);
             }
             println!("{}: {}", script.name, script.description);
+            if !script.usage.is_empty() {
+                println!("\n{}", style("Usage:").bold());
+                for line in script.usage.lines() {
+                    println!("    {}", line);
+                }
+            }
         }
         None => {
             println!("Script {} not found", name);
@@ -1,12 +1,20 @@
-use crate::file_management::{
-    database::{db_conn, scripts::get_all_scripts},
-    Script,
-};
+use crate::file_management::{database::scripts::get_all_scripts, Script};
+use rusqlite::Connection;
 
 pub mod add;
+pub mod check;
 pub mod edit;
+pub mod header;
+pub mod history;
+pub mod interpreter;
+pub mod launcher;
 pub mod list;
+pub mod project;
 pub mod remove;
+pub mod runs;
+pub mod schedule;
+pub mod status;
+pub mod template;
 pub mod update;
 
 pub fn confirm_script(script: &Script) -> bool {
@@ -14,10 +22,8 @@ pub fn confirm_script(script: &Script) -> bool {
     crate::helpers::questions::yesno!(format!("Did you mean {}?", script.name)).unwrap()
 }
 
-pub fn fuzzy_get_script(db_path: &str, name: &str) -> Option<Script> {
-    let conn = db_conn(db_path);
-
-    let scripts = get_all_scripts(&conn);
+pub fn fuzzy_get_script(conn: &Connection, name: &str) -> Option<Script> {
+    let scripts = get_all_scripts(conn);
     let mut closest_match: Option<Script> = None;
     let mut closest_distance = usize::MAX;
 
@@ -0,0 +1,393 @@
+use std::path::{Path, PathBuf};
+
+use console::style;
+
+use super::{
+    add::link_script,
+    header::{check_requires, header_group_id, read_header},
+    interpreter::detect_script_type,
+    launcher::prepare_script,
+};
+use crate::{
+    commands::{collisions::check_script_name, Context},
+    error,
+    file_management::{
+        database::scripts::{
+            add_script as add_script_to_database, update_script as update_script_in_database,
+        },
+        Script,
+    },
+    helpers::questions::{get_filepath, yesno},
+    info, success,
+};
+
+/// Directory a project's files are kept in, next to its launcher
+pub fn project_dir(script: &Script) -> PathBuf {
+    Path::new(&script.path)
+        .parent()
+        .unwrap_or(Path::new(""))
+        .join("project")
+}
+
+/// Copy the directory `source` to `destination`, recreating symlinks instead of following them
+/// so a project's venv stays intact
+fn copy_tree(source: &Path, destination: &Path) -> std::io::Result<()> {
+    std::fs::create_dir_all(destination)?;
+    for entry in std::fs::read_dir(source)? {
+        let entry = entry?;
+        let target = destination.join(entry.file_name());
+        let file_type = entry.file_type()?;
+
+        if file_type.is_symlink() {
+            link_script(&std::fs::read_link(entry.path())?, &target)?;
+        } else if file_type.is_dir() {
+            copy_tree(&entry.path(), &target)?;
+        } else {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
+
+/// Copy or link the project at `source` to `destination`
+fn store_project(source: &Path, destination: &Path, link: bool) -> std::io::Result<()> {
+    if link {
+        link_script(source, destination)
+    } else {
+        copy_tree(source, destination)
+    }
+}
+
+/// Check `entry_point` is a file inside `dir`
+fn check_entry_point(dir: &Path, entry_point: &str) -> Result<(), String> {
+    let relative = Path::new(entry_point);
+    if relative.is_absolute()
+        || relative
+            .components()
+            .any(|part| part == std::path::Component::ParentDir)
+    {
+        return Err(format!(
+            "Entry point {} has to be a path inside the project",
+            entry_point
+        ));
+    }
+    if !dir.join(relative).is_file() {
+        return Err(format!(
+            "Could not find entry point {} in {}",
+            entry_point,
+            dir.display()
+        ));
+    }
+    Ok(())
+}
+
+/// Add the directory `project_path` as a script that runs its file `entry_point`
+///
+/// The directory is copied, or with `link` symlinked, to the project directory of
+/// `scripts/<name>/` and a launcher named after the project is put next to it. The description and
+/// group are read from the header of the entry point when they aren't given.
+pub fn add_project(
+    ctx: &Context,
+    project_path: &str,
+    entry_point: Option<&str>,
+    description: &str,
+    group_id: Option<i32>,
+    strict: bool,
+    link: bool,
+) {
+    let source = match std::fs::canonicalize(project_path) {
+        Ok(source) => source,
+        Err(err) => {
+            error!(format!("Could not find {}: {}", project_path, err));
+            return;
+        }
+    };
+    let entry_point = match entry_point {
+        Some(entry_point) => entry_point,
+        None => {
+            error!(format!(
+                "Use {} to choose the file to run in {}",
+                style("--entry").bold(),
+                project_path
+            ));
+            return;
+        }
+    };
+    if let Err(err) = check_entry_point(&source, entry_point) {
+        error!(err);
+        return;
+    }
+
+    let name = match source.file_name().and_then(|name| name.to_str()) {
+        Some(name) => name.to_string(),
+        None => {
+            error!("Could not get a name for the project");
+            return;
+        }
+    };
+    if !check_script_name(ctx, &name, strict) {
+        return;
+    }
+
+    let script_dir = ctx.scripts_dir().join(&name);
+    // Anything that was already there is left alone if adding fails
+    let existing = [
+        &script_dir,
+        &script_dir.join("project"),
+        &script_dir.join(&name),
+    ]
+    .map(|path| path.symlink_metadata().is_ok());
+    let remove_created = || {
+        if !existing[0] {
+            let _ = std::fs::remove_dir_all(&script_dir);
+            return;
+        }
+        if !existing[1] {
+            let _ = std::fs::remove_dir_all(script_dir.join("project"));
+        }
+        if !existing[2] {
+            let _ = std::fs::remove_file(script_dir.join(&name));
+        }
+    };
+    if std::fs::create_dir_all(&script_dir).is_err() {
+        error!("Issue creating script directory");
+        return;
+    }
+
+    let header = read_header(&source.join(entry_point));
+    check_requires(ctx, &header);
+    let description = match (description, &header.description) {
+        ("", Some(header_description)) => header_description.clone(),
+        _ => description.to_string(),
+    };
+
+    let script = Script {
+        name: name.clone(),
+        path: script_dir.join(&name).to_str().unwrap().to_string(),
+        description,
+        enabled: true,
+        group_id: group_id
+            .or_else(|| header_group_id(ctx, &header))
+            .unwrap_or(1),
+        source_path: source.to_str().map(String::from),
+        source_hash: None,
+        linked: link,
+        entry_point: Some(entry_point.to_string()),
+        type_: detect_script_type(&source.join(entry_point)),
+        issues: Vec::new(),
+        usage: header.usage.unwrap_or_default(),
+        log_runs: false,
+        schedule: None,
+    };
+
+    let project = project_dir(&script);
+    if let Err(err) = store_project(&source, &project, link) {
+        error!(format!(
+            "Issue copying project to scripts directory: {}",
+            err
+        ));
+        remove_created();
+        return;
+    }
+
+    let result = ctx.commit(|tx| {
+        add_script_to_database(tx, &script)?;
+        prepare_script(ctx, &script, Some(&project))
+    });
+    if let Err(err) = result {
+        error!(format!("Issue adding script: {}", err));
+        remove_created();
+        let _ = std::fs::remove_dir_all(ctx.venvs_dir().join(&script.name));
+        return;
+    }
+
+    success!(format!(
+        "Project {} added, running {}",
+        style(&name).bold(),
+        entry_point
+    ));
+}
+
+/// Replace the copy of a project with the directory at `update_path`, or the one it was added
+/// from
+pub fn update_project(ctx: &Context, script: &Script, update_path: Option<&str>, yes: bool) {
+    if script.linked {
+        error!(format!(
+            "Script {} is a link to {}, edit that directory instead",
+            style(&script.name).bold(),
+            script.source_path.as_deref().unwrap_or_default()
+        ));
+        return;
+    }
+
+    let recorded_source = script
+        .source_path
+        .clone()
+        .filter(|path| Path::new(path).is_dir());
+    let source = match (update_path, recorded_source) {
+        (Some(path), _) => path.to_string(),
+        (None, Some(path)) => {
+            info!(format!("Updating from {}", style(&path).bold()));
+            path
+        }
+        (None, None) => match get_filepath!("Updated project directory") {
+            Ok(path) => path,
+            Err(_) => return,
+        },
+    };
+    let source = match std::fs::canonicalize(&source) {
+        Ok(source) if source.is_dir() => source,
+        _ => {
+            error!(format!("{} is not a directory", source));
+            return;
+        }
+    };
+    if let Err(err) = check_entry_point(&source, script.entry_point.as_deref().unwrap_or_default())
+    {
+        error!(err);
+        return;
+    }
+
+    if !yes
+        && !yesno!(format!(
+            "Replace the files of {} with {}?",
+            script.name,
+            source.display()
+        ))
+        .unwrap_or(false)
+    {
+        println!("{}", style("Aborting").yellow());
+        return;
+    }
+
+    // Copy beside the current project and swap them once the copy is complete
+    let project = project_dir(script);
+    let new_project = project.with_file_name(".project.tmp");
+    let old_project = project.with_file_name(".project.old");
+    let _ = std::fs::remove_dir_all(&new_project);
+    if let Err(err) = copy_tree(&source, &new_project) {
+        error!(format!("Could not copy {}: {}", source.display(), err));
+        let _ = std::fs::remove_dir_all(&new_project);
+        return;
+    }
+
+    let entry_point = script.entry_point.as_deref().unwrap_or_default();
+    let updated = Script {
+        source_path: source.to_str().map(String::from),
+        type_: detect_script_type(&source.join(entry_point)),
+        usage: read_header(&source.join(entry_point))
+            .usage
+            .unwrap_or_default(),
+        ..script.clone()
+    };
+    let result = ctx.commit(|tx| {
+        update_script_in_database(tx, &script.name, &updated)?;
+        std::fs::rename(&project, &old_project)
+            .map_err(|err| format!("Could not replace project: {}", err))?;
+        if let Err(err) = std::fs::rename(&new_project, &project) {
+            let _ = std::fs::rename(&old_project, &project);
+            return Err(format!("Could not replace project: {}", err));
+        }
+        prepare_script(ctx, &updated, Some(&project))
+    });
+    if let Err(err) = result {
+        error!(format!("Could not update script: {}", err));
+        let _ = std::fs::remove_dir_all(&new_project);
+        // The swap may have happened before the error, put the old files back
+        if old_project.exists() {
+            let _ = std::fs::remove_dir_all(&project);
+            let _ = std::fs::rename(&old_project, &project);
+        }
+        return;
+    }
+
+    let _ = std::fs::remove_dir_all(&old_project);
+    success!(format!("Project {} updated", style(&script.name).bold()));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_management::database::scripts::get_script_by_name;
+
+    use super::*;
+
+    #[test]
+    fn add_project_test() {
+        let test_dir = Path::new("add_project_test");
+        let db_file = "add_project_test/nym.db";
+        let rc_file = "add_project_test/nymrc";
+        let source = test_dir.join("source/project_tool");
+        std::fs::create_dir_all(source.join("lib")).expect("Error creating test dirs");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        std::fs::write(
+            source.join("run.sh"),
+            "#!/bin/sh\ncat \"$NYM_PROJECT_DIR/lib/data.txt\" \"$1\"\n",
+        )
+        .unwrap();
+        std::fs::write(source.join("lib/data.txt"), "data\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let executable = std::fs::Permissions::from_mode(0o755);
+            std::fs::set_permissions(source.join("run.sh"), executable).unwrap();
+        }
+
+        let source_path = source.to_str().unwrap();
+        add_project(&ctx, source_path, None, "", None, false, false);
+        add_project(&ctx, source_path, Some("../run.sh"), "", None, false, false);
+        assert!(get_script_by_name(&ctx.conn, "project_tool").is_err());
+
+        add_project(&ctx, source_path, Some("run.sh"), "", None, false, false);
+        let script = get_script_by_name(&ctx.conn, "project_tool").unwrap();
+        assert_eq!(script.entry_point.as_deref(), Some("run.sh"));
+        assert_eq!(
+            script.path,
+            "add_project_test/scripts/project_tool/project_tool"
+        );
+        assert_eq!(
+            std::fs::read_to_string(project_dir(&script).join("lib/data.txt")).unwrap(),
+            "data\n"
+        );
+
+        // The launcher runs the entry point with the arguments passed, which are relative to the
+        // caller's directory rather than the project's
+        #[cfg(unix)]
+        {
+            std::fs::write(source.join("lib/data.txt"), "new data\n").unwrap();
+            std::fs::write(test_dir.join("arg.txt"), "arg\n").unwrap();
+            let launcher = std::fs::canonicalize(&script.path).unwrap();
+            let output = std::process::Command::new(launcher)
+                .arg("arg.txt")
+                .current_dir(test_dir)
+                .output()
+                .unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), "data\narg\n");
+
+            update_project(&ctx, &script, None, true);
+            let output = std::process::Command::new(&script.path).output().unwrap();
+            assert_eq!(String::from_utf8_lossy(&output.stdout), "new data\n");
+        }
+
+        // A failed add doesn't delete files that were there before it
+        let clash = test_dir.join("source/clash");
+        std::fs::create_dir_all(&clash).unwrap();
+        std::fs::write(clash.join("run.sh"), "#!/bin/sh\n").unwrap();
+        let kept = ctx.scripts_dir().join("clash/project");
+        std::fs::create_dir_all(kept.parent().unwrap()).unwrap();
+        std::fs::write(&kept, "kept\n").unwrap();
+        add_project(
+            &ctx,
+            clash.to_str().unwrap(),
+            Some("run.sh"),
+            "",
+            None,
+            false,
+            false,
+        );
+        assert!(get_script_by_name(&ctx.conn, "clash").is_err());
+        assert_eq!(std::fs::read_to_string(&kept).unwrap(), "kept\n");
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
@@ -1,17 +1,21 @@
 use crate::{
-    commands::scripts::{confirm_script, fuzzy_get_script},
+    commands::{
+        scripts::{confirm_script, fuzzy_get_script, runs::logs_dir, schedule::apply_schedules},
+        Context,
+    },
     error,
     file_management::{
-        database::{db_conn, scripts::remove_script as remove_script_from_database},
-        update_runcom,
+        database::{
+            script_runs::remove_script_runs, script_versions::remove_script_versions,
+            scripts::remove_script as remove_script_from_database,
+        },
+        Script,
     },
     success,
 };
 
-pub fn remove_script(rc_file: &str, db_file: &str, script_name: &str, force: bool) {
-    let conn = db_conn(db_file);
-
-    let script = match fuzzy_get_script(db_file, script_name) {
+pub fn remove_script(ctx: &Context, script_name: &str, force: bool) {
+    let script = match fuzzy_get_script(&ctx.conn, script_name) {
         Some(script) => script,
         None => {
             error!("Script not found");
@@ -34,25 +38,11 @@ pub fn remove_script(rc_file: &str, db_file: &str, script_name: &str, force: boo
         std::process::exit(1);
     }
 
-    if remove_script_from_database(&conn, &script.name).is_err() {
-        error!("Could not remove script from database");
-        return;
-    }
-
-    if std::fs::remove_dir_all(
-        std::path::Path::new(&script.path)
-            .parent()
-            .unwrap()
-            .to_str()
-            .unwrap(),
-    )
-    .is_err()
-    {
-        error!("Could not remove script");
+    if let Err(err) = remove_scripts(ctx, &[script]) {
+        error!(err);
         return;
     }
 
-    update_runcom(rc_file, db_file);
     success!("Script removed successfully");
     println!(
         "Please run {} to activate changes",
@@ -60,11 +50,76 @@ pub fn remove_script(rc_file: &str, db_file: &str, script_name: &str, force: boo
     );
 }
 
+/// Remove `scripts` from the database along with their versions and runs, then delete their
+/// files, venvs and logs and stop running them on a schedule
+///
+/// Everything a script leaves behind goes, a script added later can be given the same id.
+pub fn remove_scripts(ctx: &Context, scripts: &[Script]) -> Result<(), String> {
+    ctx.commit(|tx| {
+        for script in scripts {
+            remove_script_versions(tx, &script.name)?;
+            remove_script_runs(tx, &script.name)?;
+            remove_script_from_database(tx, &script.name)?;
+        }
+        Ok(())
+    })
+    .map_err(|err| format!("Could not remove script from database: {}", err))?;
+
+    let mut errors = Vec::new();
+    for script in scripts {
+        if let Err(err) = remove_script_files(script) {
+            errors.push(err);
+        }
+        let venv = ctx.venvs_dir().join(&script.name);
+        if venv.exists() {
+            let _ = std::fs::remove_dir_all(venv);
+        }
+        let logs = logs_dir(ctx, &script.name);
+        if logs.exists() {
+            let _ = std::fs::remove_dir_all(logs);
+        }
+    }
+    if scripts.iter().any(|script| script.schedule.is_some()) {
+        if let Err(err) = apply_schedules(ctx) {
+            errors.push(err);
+        }
+    }
+
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(errors.join("\n")),
+    }
+}
+
+/// Delete the managed copy of a script once it has been removed from the database
+///
+/// For a linked script only the link goes, `remove_dir_all` doesn't follow symlinks.
+fn remove_script_files(script: &Script) -> Result<(), String> {
+    let script_dir = match std::path::Path::new(&script.path).parent() {
+        Some(dir) => dir,
+        None => {
+            return Err(format!(
+                "Could not find directory of script {}",
+                script.name
+            ))
+        }
+    };
+
+    std::fs::remove_dir_all(script_dir)
+        .map_err(|err| format!("Could not remove script {}: {}", script.name, err))
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
-        commands::scripts::add::add_script, file_management::database::scripts::get_script_by_name,
+        commands::scripts::{add::add_script, check::FixIssues},
+        file_management::database::{
+            script_runs::{get_script_runs, start_script_run},
+            script_versions::get_script_versions,
+            scripts::get_script_by_name,
+        },
     };
+    use std::path::Path;
 
     use super::*;
 
@@ -77,7 +132,7 @@ mod tests {
         // create test folder
         assert!(std::fs::create_dir(test_dir).is_ok());
 
-        let conn = db_conn(db_file);
+        let ctx = Context::new(rc_file, db_file).unwrap();
         assert!(std::path::Path::new(db_file).exists());
 
         // Create test script
@@ -85,9 +140,9 @@ mod tests {
         let script = r#"echo "test script""#;
         std::fs::write(script_path, script).expect("Error creating test script");
 
-        add_script(rc_file, db_file, script_path, "", 1);
+        add_script(&ctx, script_path, "", None, false, false, FixIssues::Never);
 
-        let script = get_script_by_name(&conn, "test_script");
+        let script = get_script_by_name(&ctx.conn, "test_script");
         assert!(script.is_ok());
         assert_eq!(script.clone().unwrap().name, "test_script");
         assert_eq!(
@@ -95,9 +150,9 @@ mod tests {
             "remove_script_test/scripts/test_script/test_script.sh"
         );
 
-        remove_script(rc_file, db_file, "test_script", true);
+        remove_script(&ctx, "test_script", true);
 
-        let script = get_script_by_name(&conn, "test_script");
+        let script = get_script_by_name(&ctx.conn, "test_script");
         assert!(script.is_err());
 
         // Clean up
@@ -106,4 +161,78 @@ mod tests {
         }
         std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
     }
+
+    #[test]
+    fn remove_scripts_test() {
+        let test_dir = "remove_scripts_test";
+        let db_file = "remove_scripts_test/nym.db";
+        let rc_file = "remove_scripts_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        for name in ["first", "second"] {
+            let source = format!("remove_scripts_test/{}.sh", name);
+            std::fs::write(&source, "#!/bin/sh\necho removed\n").unwrap();
+            add_script(&ctx, &source, "", None, false, false, FixIssues::Always);
+        }
+        // Adding a script saves its first version
+        assert_eq!(get_script_versions(&ctx.conn, "first").len(), 1);
+        start_script_run(&ctx.conn, "first", 0, "").unwrap();
+        std::fs::create_dir_all(logs_dir(&ctx, "first")).unwrap();
+        std::fs::write(logs_dir(&ctx, "first").join("1.log"), "removed\n").unwrap();
+
+        let scripts = vec![
+            get_script_by_name(&ctx.conn, "first").unwrap(),
+            get_script_by_name(&ctx.conn, "second").unwrap(),
+        ];
+        remove_scripts(&ctx, &scripts).unwrap();
+        assert!(get_script_by_name(&ctx.conn, "second").is_err());
+        assert!(!Path::new(&scripts[0].path).exists());
+        assert!(!logs_dir(&ctx, "first").exists());
+
+        // A script added under the same name starts without the old one's history
+        add_script(
+            &ctx,
+            "remove_scripts_test/first.sh",
+            "",
+            None,
+            false,
+            false,
+            FixIssues::Always,
+        );
+        assert_eq!(get_script_versions(&ctx.conn, "first").len(), 1);
+        assert!(get_script_runs(&ctx.conn, "first").is_empty());
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn remove_linked_script_test() {
+        let test_dir = "remove_linked_script_test";
+        let db_file = "remove_linked_script_test/nym.db";
+        let rc_file = "remove_linked_script_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        let target = "remove_linked_script_test/linked_script.sh";
+        std::fs::write(target, "echo linked").unwrap();
+        add_script(&ctx, target, "", None, false, true, FixIssues::Never);
+
+        let script = get_script_by_name(&ctx.conn, "linked_script").unwrap();
+        assert!(script.linked);
+        let link = std::path::Path::new(&script.path);
+        assert!(link.symlink_metadata().unwrap().file_type().is_symlink());
+        assert_eq!(
+            std::fs::read_link(link).unwrap(),
+            std::fs::canonicalize(target).unwrap()
+        );
+
+        // Only the link is removed, the file it points to stays
+        remove_script(&ctx, "linked_script", true);
+        assert!(link.symlink_metadata().is_err());
+        assert_eq!(std::fs::read_to_string(target).unwrap(), "echo linked");
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
 }
@@ -0,0 +1,400 @@
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use console::style;
+
+use crate::{
+    commands::{exec::exit_code, Context},
+    error,
+    file_management::{
+        database::{
+            script_runs::{
+                finish_script_run, get_script_runs, remove_old_script_runs, start_script_run,
+            },
+            scripts::update_script,
+        },
+        Script, ScriptRun,
+    },
+    success, warning,
+};
+
+use super::{edit::get_script, history::format_timestamp};
+
+/// Number of runs of a script kept, older runs and their logs are deleted
+pub const KEPT_RUNS: usize = 20;
+
+/// Directory the output of a script's runs is kept in
+pub fn logs_dir(ctx: &Context, script_name: &str) -> PathBuf {
+    ctx.nym_dir().join("logs").join(script_name)
+}
+
+fn log_path(logs_dir: &Path, run: i32) -> PathBuf {
+    logs_dir.join(format!("{}.log", run))
+}
+
+/// Copy everything read from `from` to `to` and to the shared `log`
+fn tee(mut from: impl Read, mut to: impl Write, log: &Mutex<File>) {
+    let mut buffer = [0; 8192];
+    while let Ok(read) = from.read(&mut buffer) {
+        if read == 0 {
+            break;
+        }
+        let _ = to.write_all(&buffer[..read]).and_then(|_| to.flush());
+        if let Ok(mut log) = log.lock() {
+            let _ = log.write_all(&buffer[..read]);
+        }
+    }
+}
+
+/// Delete the logs of runs that are no longer recorded
+fn remove_old_logs(logs_dir: &Path, runs: &[ScriptRun]) -> std::io::Result<()> {
+    for entry in std::fs::read_dir(logs_dir)? {
+        let path = entry?.path();
+        let recorded = path
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .and_then(|stem| stem.parse::<i32>().ok())
+            .is_some_and(|run| runs.iter().any(|recorded| recorded.run == run));
+        if !recorded {
+            std::fs::remove_file(path)?;
+        }
+    }
+    Ok(())
+}
+
+/// Keep only the latest `keep` runs of a script and their logs
+fn rotate_runs(ctx: &Context, script_name: &str, keep: usize) -> Result<(), String> {
+    remove_old_script_runs(&ctx.conn, script_name, keep)?;
+    remove_old_logs(
+        &logs_dir(ctx, script_name),
+        &get_script_runs(&ctx.conn, script_name),
+    )
+    .map_err(|err| format!("Could not delete old logs: {}", err))
+}
+
+/// Run `command` for `script` when its run can't be logged, `reason` says why
+fn run_unlogged(script: &Script, mut command: Command, reason: &str) -> Result<i32, String> {
+    warning!(format!(
+        "{}, running {} without logging it",
+        reason, script.name
+    ));
+    command
+        .status()
+        .map(exit_code)
+        .map_err(|err| format!("Could not run {}: {}", script.name, err))
+}
+
+/// Run `command` for `script`, recording the run and copying its output to a log as well as the
+/// terminal, returns the exit code
+///
+/// stdout and stderr are pipes rather than the terminal, so the script may leave out colours. If
+/// the run can't be recorded, e.g. another nym command is holding the database, the script is
+/// still run, just without a log.
+pub fn run_logged(
+    ctx: &Context,
+    script: &Script,
+    command: Command,
+    args: &[String],
+) -> Result<i32, String> {
+    record_run(
+        ctx,
+        script,
+        command,
+        args,
+        std::io::stdout(),
+        std::io::stderr(),
+    )
+}
+
+/// [`run_logged`], copying the output to `stdout` and `stderr` instead of the terminal's
+fn record_run(
+    ctx: &Context,
+    script: &Script,
+    mut command: Command,
+    args: &[String],
+    stdout: impl Write + Send,
+    stderr: impl Write + Send,
+) -> Result<i32, String> {
+    let started_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs() as i64);
+    let quoted_args = args
+        .iter()
+        .map(|arg| ctx.shell.quote(arg))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let dir = logs_dir(ctx, &script.name);
+    let run = std::fs::create_dir_all(&dir)
+        .map_err(|err| format!("Could not create {}: {}", dir.display(), err))
+        .and_then(|_| {
+            start_script_run(&ctx.conn, &script.name, started_at, &quoted_args)
+                .map_err(|err| format!("Could not record run: {}", err))
+        });
+    let run = match run {
+        Ok(run) => run,
+        Err(err) => return run_unlogged(script, command, &err),
+    };
+    let log = match File::create(log_path(&dir, run)) {
+        Ok(log) => Mutex::new(log),
+        Err(err) => {
+            let start = Instant::now();
+            let reason = format!("Could not create log of run {}: {}", run, err);
+            let result = run_unlogged(script, command, &reason);
+            let duration_ms = start.elapsed().as_millis() as i64;
+            let code = *result.as_ref().unwrap_or(&126);
+            let _ = finish_script_run(&ctx.conn, &script.name, run, duration_ms, code);
+            return result;
+        }
+    };
+
+    let start = Instant::now();
+    let status = match command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+    {
+        Ok(mut child) => {
+            let from_stdout = child.stdout.take();
+            let from_stderr = child.stderr.take();
+            std::thread::scope(|scope| {
+                let log = &log;
+                if let Some(from) = from_stdout {
+                    scope.spawn(move || tee(from, stdout, log));
+                }
+                if let Some(from) = from_stderr {
+                    scope.spawn(move || tee(from, stderr, log));
+                }
+                child.wait()
+            })
+        }
+        Err(err) => Err(err),
+    };
+    let duration_ms = start.elapsed().as_millis() as i64;
+
+    // A run that couldn't start is kept too, with the reason as its output
+    let (code, result) = match status {
+        Ok(status) => {
+            let code = exit_code(status);
+            (code, Ok(code))
+        }
+        Err(err) => {
+            let message = format!("Could not run {}: {}", script.name, err);
+            if let Ok(mut log) = log.lock() {
+                let _ = writeln!(log, "{}", message);
+            }
+            (126, Err(message))
+        }
+    };
+    if let Err(err) = finish_script_run(&ctx.conn, &script.name, run, duration_ms, code) {
+        warning!(format!("Could not record run: {}", err));
+    }
+    if let Err(err) = rotate_runs(ctx, &script.name, KEPT_RUNS) {
+        warning!(err);
+    }
+    result
+}
+
+fn format_duration(duration_ms: i64) -> String {
+    match duration_ms {
+        ms if ms < 1000 => format!("{}ms", ms),
+        ms if ms < 60_000 => format!("{:.1}s", ms as f64 / 1000.0),
+        ms => format!("{}m {:02}s", ms / 60_000, ms / 1000 % 60),
+    }
+}
+
+/// Turn recording runs of a script on or off
+pub fn set_run_logging(ctx: &Context, name: &str, enabled: bool) {
+    let script = match get_script(ctx, name) {
+        Ok(script) => script,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let updated = Script {
+        log_runs: enabled,
+        ..script.clone()
+    };
+    if let Err(err) =
+        ctx.commit(|tx| update_script(tx, &script.name, &updated).map_err(String::from))
+    {
+        error!(format!("Could not update script: {}", err));
+        return;
+    }
+    if enabled {
+        success!(format!(
+            "Runs of {} through {} will be logged",
+            style(&script.name).bold(),
+            style("`nym exec`").bold()
+        ));
+    } else {
+        success!(format!(
+            "Runs of {} will no longer be logged",
+            style(&script.name).bold()
+        ));
+    }
+}
+
+/// List the recorded runs of a script
+pub fn script_runs(ctx: &Context, name: &str) {
+    let script = match get_script(ctx, name) {
+        Ok(script) => script,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let runs = get_script_runs(&ctx.conn, &script.name);
+    if runs.is_empty() {
+        if script.log_runs {
+            warning!(format!(
+                "{} has not been run yet",
+                style(&script.name).bold()
+            ));
+        } else {
+            warning!(format!(
+                "Runs of {} are not logged, turn it on with {}",
+                style(&script.name).bold(),
+                style(format!("`nym script logging {} on`", script.name)).bold()
+            ));
+        }
+        return;
+    }
+
+    for run in &runs {
+        let exit = match run.exit_code {
+            Some(0) => style("0".to_string()).green(),
+            Some(code) => style(code.to_string()).red(),
+            None => style("-".to_string()).dim(),
+        };
+        println!(
+            "{:>4}  {}  {:>8}  {:>3}  {}",
+            style(run.run).bold(),
+            format_timestamp(run.started_at),
+            run.duration_ms
+                .map_or("running".to_string(), format_duration),
+            exit,
+            run.args
+        );
+    }
+}
+
+/// Print the output of a run of a script, the latest if `run` isn't given
+pub fn script_log(ctx: &Context, name: &str, run: Option<i32>) {
+    let script = match get_script(ctx, name) {
+        Ok(script) => script,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let runs = get_script_runs(&ctx.conn, &script.name);
+    let run = match run {
+        Some(run) => runs.iter().find(|recorded| recorded.run == run),
+        None => runs.last(),
+    };
+    let run = match run {
+        Some(run) => run,
+        None => {
+            error!(format!(
+                "There is no such run of {}, see {}",
+                style(&script.name).bold(),
+                style(format!("`nym script runs {}`", script.name)).bold()
+            ));
+            return;
+        }
+    };
+
+    match std::fs::read(log_path(&logs_dir(ctx, &script.name), run.run)) {
+        Ok(log) => {
+            let _ = std::io::stdout().write_all(&log);
+        }
+        Err(err) => error!(format!("Could not read log of run {}: {}", run.run, err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::{
+            exec::exec,
+            scripts::{add::add_script, check::FixIssues},
+        },
+        file_management::database::scripts::get_script_by_name,
+    };
+
+    use super::*;
+
+    #[test]
+    fn script_runs_test() {
+        let test_dir = "script_runs_test";
+        let db_file = "script_runs_test/nym.db";
+        let rc_file = "script_runs_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        // Output only for a first argument, runs that print to the terminal are given none
+        let source = "script_runs_test/logged.sh";
+        std::fs::write(
+            source,
+            "#!/bin/sh\nif [ -n \"$1\" ]; then echo out \"$1\"; echo err >&2; fi\nexit \"$2\"\n",
+        )
+        .unwrap();
+        add_script(&ctx, source, "", None, false, false, FixIssues::Always);
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+
+        // Nothing is recorded until logging is turned on
+        assert_eq!(exec(&ctx, "logged", &args(&["", "0"])), 0);
+        assert!(get_script_runs(&ctx.conn, "logged").is_empty());
+
+        set_run_logging(&ctx, "logged", true);
+        let script = get_script_by_name(&ctx.conn, "logged").unwrap();
+        assert!(script.log_runs);
+        assert_eq!(exec(&ctx, "logged", &args(&["", "0"])), 0);
+        let mut command = Command::new(&script.path);
+        command.args(["a b", "3"]);
+        let (mut stdout, mut stderr) = (Vec::new(), Vec::new());
+        let code = record_run(
+            &ctx,
+            &script,
+            command,
+            &args(&["a b", "3"]),
+            &mut stdout,
+            &mut stderr,
+        );
+        assert_eq!(code, Ok(3));
+        assert_eq!(stdout, b"out a b\n");
+        assert_eq!(stderr, b"err\n");
+
+        let runs = get_script_runs(&ctx.conn, "logged");
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].args, "'' '0'");
+        assert_eq!(runs[0].exit_code, Some(0));
+        assert_eq!(runs[1].args, "'a b' '3'");
+        assert_eq!(runs[1].exit_code, Some(3));
+        assert!(runs[1].duration_ms.is_some());
+        let log = std::fs::read_to_string(log_path(&logs_dir(&ctx, "logged"), 2)).unwrap();
+        assert!(log.contains("out a b\n"));
+        assert!(log.contains("err\n"));
+
+        rotate_runs(&ctx, "logged", 1).unwrap();
+        assert_eq!(get_script_runs(&ctx.conn, "logged").len(), 1);
+        assert!(!log_path(&logs_dir(&ctx, "logged"), 1).exists());
+        assert!(log_path(&logs_dir(&ctx, "logged"), 2).exists());
+
+        // A run that can't be logged still happens
+        std::fs::remove_dir_all(logs_dir(&ctx, "logged")).unwrap();
+        std::fs::write(logs_dir(&ctx, "logged"), "").unwrap();
+        assert_eq!(exec(&ctx, "logged", &args(&["", "4"])), 4);
+        assert_eq!(get_script_runs(&ctx.conn, "logged").len(), 1);
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
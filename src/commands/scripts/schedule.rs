@@ -0,0 +1,606 @@
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+
+use console::style;
+use rusqlite::Connection;
+
+use crate::{
+    commands::{collisions::find_executable, Context},
+    error,
+    file_management::database::scripts::{get_all_scripts, update_script},
+    helpers::shell::Shell,
+    success, warning,
+};
+
+use super::edit::get_script;
+
+const WEEKDAYS: [&str; 7] = [
+    "sunday",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+];
+
+/// Cron's number for a day of the week, from its name or first three letters
+fn weekday(name: &str) -> Result<usize, String> {
+    let name = name.to_ascii_lowercase();
+    WEEKDAYS
+        .iter()
+        .position(|day| name.len() >= 3 && day.starts_with(&name))
+        .ok_or(format!("{} is not a day of the week", name))
+}
+
+/// Cron's weekday field for a list like `Mon,Wed` or `Mon..Fri`
+fn cron_weekdays(value: &str) -> Result<String, String> {
+    let mut days = Vec::new();
+    for part in value.split(',') {
+        match part.split_once("..") {
+            Some((first, last)) => {
+                let (first, last) = (weekday(first)?, weekday(last)?);
+                // Ranges can wrap around the end of the week, e.g. Sat..Sun
+                let length = (last + 7 - first) % 7;
+                days.extend((0..=length).map(|offset| (first + offset) % 7));
+            }
+            None => days.push(weekday(part)?),
+        }
+    }
+    Ok(days
+        .iter()
+        .map(|day| day.to_string())
+        .collect::<Vec<_>>()
+        .join(","))
+}
+
+/// Cron field for one component of a calendar spec, e.g. `*`, `9`, `1,15`, `8..18` or `0/15`
+fn cron_field(value: &str, min: u32, max: u32) -> Result<String, String> {
+    let number = |text: &str| match text.parse::<u32>() {
+        Ok(number) if (min..=max).contains(&number) => Ok(number),
+        _ => Err(format!("{} is not between {} and {}", text, min, max)),
+    };
+
+    let mut fields = Vec::new();
+    for part in value.split(',') {
+        let (base, step) = match part.split_once('/') {
+            Some((base, step)) => match step.parse::<u32>() {
+                Ok(step) if step > 0 => (base, Some(step)),
+                _ => return Err(format!("{} is not a valid repetition", step)),
+            },
+            None => (part, None),
+        };
+        let mut field = match base.split_once("..") {
+            _ if base == "*" => "*".to_string(),
+            Some((first, last)) => format!("{}-{}", number(first)?, number(last)?),
+            // systemd repeats from a start value to the end, cron needs the range spelled out
+            None if step.is_some() => format!("{}-{}", number(base)?, max),
+            None => number(base)?.to_string(),
+        };
+        if let Some(step) = step {
+            field.push_str(&format!("/{}", step));
+        }
+        fields.push(field);
+    }
+    Ok(fields.join(","))
+}
+
+/// Translate a calendar spec into the five fields of a crontab line
+///
+/// Specs are the subset of systemd's `OnCalendar` syntax cron can express: a shorthand like
+/// `daily`, or `[weekdays] [[*-]month-day] [hour:minute[:00]]` such as `Mon..Fri 09:00` or
+/// `*-*-01 *:0/30`. Anything this accepts means the same to systemd.
+pub fn calendar_to_cron(spec: &str) -> Result<String, String> {
+    let shorthand = match spec.trim().to_ascii_lowercase().as_str() {
+        "minutely" => Some("* * * * *"),
+        "hourly" => Some("0 * * * *"),
+        "daily" => Some("0 0 * * *"),
+        "weekly" => Some("0 0 * * 1"),
+        "monthly" => Some("0 0 1 * *"),
+        "yearly" | "annually" => Some("0 0 1 1 *"),
+        _ => None,
+    };
+    if let Some(shorthand) = shorthand {
+        return Ok(shorthand.to_string());
+    }
+
+    let invalid = |reason: String| format!("Could not understand schedule {}: {}", spec, reason);
+    let mut words = spec.split_whitespace().peekable();
+    let mut fields = ["0", "0", "*", "*", "*"].map(String::from);
+
+    if let Some(word) = words.next_if(|word| !word.contains([':', '-'])) {
+        fields[4] = cron_weekdays(word).map_err(invalid)?;
+    }
+    if let Some(word) = words.next_if(|word| word.contains('-')) {
+        let parts = word.split('-').collect::<Vec<_>>();
+        let (month, day) = match parts[..] {
+            ["*", month, day] => (month, day),
+            [_, _, _] => return Err(invalid("only every year (*) is supported".to_string())),
+            [month, day] => (month, day),
+            _ => return Err(invalid(format!("{} is not a date", word))),
+        };
+        fields[3] = cron_field(month, 1, 12).map_err(invalid)?;
+        fields[2] = cron_field(day, 1, 31).map_err(invalid)?;
+        // systemd runs on days matching both, cron on days matching either
+        if fields[2] != "*" && fields[4] != "*" {
+            return Err(invalid(
+                "cron can't run only when both the weekday and the day match".to_string(),
+            ));
+        }
+    }
+    if let Some(word) = words.next_if(|word| word.contains(':')) {
+        let parts = word.split(':').collect::<Vec<_>>();
+        let (hour, minute) = match parts[..] {
+            [hour, minute] | [hour, minute, "0" | "00"] => (hour, minute),
+            [_, _, _] => {
+                return Err(invalid(
+                    "cron can't run at seconds past the minute".to_string(),
+                ))
+            }
+            _ => return Err(invalid(format!("{} is not a time", word))),
+        };
+        fields[1] = cron_field(hour, 0, 23).map_err(invalid)?;
+        fields[0] = cron_field(minute, 0, 59).map_err(invalid)?;
+    }
+    match words.next() {
+        Some(word) => Err(invalid(format!("did not expect {}", word))),
+        None if fields[2..] == ["*", "*", "*"] && !spec.contains(':') => {
+            Err(invalid("give a shorthand, day or time".to_string()))
+        }
+        None => Ok(fields.join(" ")),
+    }
+}
+
+/// Name shared by the service and timer of a script
+///
+/// Bytes systemd doesn't allow in unit names are written as `\xNN`, like `systemd-escape` does.
+fn unit_name(script_name: &str) -> String {
+    let mut unit = "nym-".to_string();
+    for byte in script_name.bytes() {
+        if byte.is_ascii_alphanumeric() || b":_.-".contains(&byte) {
+            unit.push(byte as char);
+        } else {
+            unit.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+    unit
+}
+
+/// `text` in a unit file setting, where `%` starts a specifier
+fn escape_specifiers(text: &str) -> String {
+    text.replace('%', "%%")
+}
+
+/// `word` as one argument of an `ExecStart` line
+fn systemd_quote(word: &str) -> String {
+    let mut quoted = "\"".to_string();
+    for c in word.chars() {
+        match c {
+            '\\' | '"' => {
+                quoted.push('\\');
+                quoted.push(c);
+            }
+            '\n' => quoted.push_str("\\n"),
+            '%' => quoted.push_str("%%"),
+            '$' => quoted.push_str("$$"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+/// Service running a script through `nym exec`, so runs are logged if the script asks for it
+fn service_unit(script_name: &str, nym: &Path) -> String {
+    format!(
+        "[Unit]\n\
+        Description=nym script {description}\n\
+        \n\
+        [Service]\n\
+        Type=oneshot\n\
+        ExecStart={nym} exec {name}\n",
+        description = escape_specifiers(script_name),
+        name = systemd_quote(script_name),
+        nym = systemd_quote(&nym.to_string_lossy())
+    )
+}
+
+fn timer_unit(script_name: &str, spec: &str) -> String {
+    format!(
+        "[Unit]\n\
+        Description=Run nym script {description} on a schedule\n\
+        \n\
+        [Timer]\n\
+        OnCalendar={spec}\n\
+        Persistent=true\n\
+        \n\
+        [Install]\n\
+        WantedBy=timers.target\n",
+        description = escape_specifiers(script_name),
+        spec = spec
+    )
+}
+
+fn is_nym_unit(file_name: &str) -> bool {
+    file_name.starts_with("nym-")
+        && (file_name.ends_with(".service") || file_name.ends_with(".timer"))
+}
+
+/// Make `unit_dir` hold a service and timer for each `(script, spec)` in `scheduled` and no
+/// other nym units, enabling and disabling the timers through `systemctl`
+///
+/// Timers that go are disabled while their unit files still exist, systemd can't disable a
+/// unit it can no longer find.
+pub fn sync_systemd(
+    unit_dir: &Path,
+    scheduled: &[(String, String)],
+    nym: &Path,
+    systemctl: impl Fn(&[&str]) -> Result<(), String>,
+) -> Result<(), String> {
+    let io_error = |err: std::io::Error| format!("Could not update systemd units: {}", err);
+    let mut wanted = Vec::new();
+    for (name, spec) in scheduled {
+        let unit = unit_name(name);
+        wanted.push((format!("{}.service", unit), service_unit(name, nym)));
+        wanted.push((format!("{}.timer", unit), timer_unit(name, spec)));
+    }
+
+    let mut stale = Vec::new();
+    if unit_dir.is_dir() {
+        for entry in std::fs::read_dir(unit_dir).map_err(io_error)? {
+            let file_name = entry.map_err(io_error)?.file_name();
+            let file_name = file_name.to_string_lossy();
+            if is_nym_unit(&file_name) && !wanted.iter().any(|(name, _)| *name == file_name) {
+                stale.push(file_name.to_string());
+            }
+        }
+    }
+    for timer in stale.iter().filter(|unit| unit.ends_with(".timer")) {
+        systemctl(&["disable", "--now", timer])?;
+    }
+    for unit in &stale {
+        std::fs::remove_file(unit_dir.join(unit)).map_err(io_error)?;
+    }
+
+    let mut changed = Vec::new();
+    for (file_name, content) in &wanted {
+        let path = unit_dir.join(file_name);
+        if std::fs::read_to_string(&path).is_ok_and(|current| current == *content) {
+            continue;
+        }
+        std::fs::create_dir_all(unit_dir).map_err(io_error)?;
+        std::fs::write(&path, content).map_err(io_error)?;
+        let timer = format!("{}.timer", file_name.rsplit_once('.').unwrap_or_default().0);
+        if !changed.contains(&timer) {
+            changed.push(timer);
+        }
+    }
+
+    if stale.is_empty() && changed.is_empty() {
+        return Ok(());
+    }
+    systemctl(&["daemon-reload"])?;
+    for timer in &changed {
+        systemctl(&["enable", timer])?;
+        // Restarting picks up a changed schedule and starts a new timer
+        systemctl(&["restart", timer])?;
+    }
+    Ok(())
+}
+
+const CRON_BEGIN: &str = "# BEGIN nym schedules";
+const CRON_END: &str = "# END nym schedules";
+
+/// Command of a crontab line, cron turns a `%` that isn't escaped into a newline
+fn cron_command(command: &str) -> String {
+    command.replace('%', "\\%")
+}
+
+/// Replace the block of nym's lines in `crontab` with one running each of `scheduled`
+pub fn replace_cron_block(
+    crontab: &str,
+    scheduled: &[(String, String)],
+    nym: &Path,
+) -> Result<String, String> {
+    let mut lines = Vec::new();
+    let mut in_block = false;
+    for line in crontab.lines() {
+        match line {
+            CRON_BEGIN => in_block = true,
+            CRON_END => in_block = false,
+            _ if !in_block => lines.push(line.to_string()),
+            _ => (),
+        }
+    }
+
+    if !scheduled.is_empty() {
+        lines.push(CRON_BEGIN.to_string());
+        // cron runs the command with sh
+        let nym = Shell::Bash.quote(&nym.to_string_lossy());
+        for (name, spec) in scheduled {
+            let command = format!("{} exec {}", nym, Shell::Bash.quote(name));
+            lines.push(format!(
+                "{} {}",
+                calendar_to_cron(spec)?,
+                cron_command(&command)
+            ));
+        }
+        lines.push(CRON_END.to_string());
+    }
+    Ok(lines.iter().map(|line| format!("{}\n", line)).collect())
+}
+
+fn run_systemctl(args: &[&str]) -> Result<(), String> {
+    match Command::new("systemctl").arg("--user").args(args).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(_) => Err(format!("`systemctl --user {}` failed", args.join(" "))),
+        Err(err) => Err(format!("Could not run systemctl: {}", err)),
+    }
+}
+
+fn sync_cron(scheduled: &[(String, String)], nym: &Path) -> Result<(), String> {
+    // `crontab -l` fails when there is no crontab yet
+    let current = Command::new("crontab")
+        .arg("-l")
+        .stderr(Stdio::null())
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .map(|output| String::from_utf8_lossy(&output.stdout).to_string())
+        .unwrap_or_default();
+    let crontab = replace_cron_block(&current, scheduled, nym)?;
+    if crontab == current {
+        return Ok(());
+    }
+
+    let mut child = Command::new("crontab")
+        .arg("-")
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|err| format!("Could not run crontab: {}", err))?;
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin
+            .write_all(crontab.as_bytes())
+            .map_err(|err| format!("Could not write crontab: {}", err))?;
+    }
+    match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        _ => Err("Could not install crontab".to_string()),
+    }
+}
+
+fn systemd_unit_dir() -> Option<PathBuf> {
+    Some(dirs::config_dir()?.join("systemd/user"))
+}
+
+/// Name and schedule of every enabled script with a schedule
+///
+/// Toggling a group toggles each of its scripts, so a script in a disabled group is disabled
+/// itself and isn't included.
+pub fn scheduled_scripts(conn: &Connection) -> Vec<(String, String)> {
+    get_all_scripts(conn)
+        .into_iter()
+        .filter(|script| script.enabled)
+        .filter_map(|script| Some((script.name, script.schedule?)))
+        .collect()
+}
+
+/// Install timers for the enabled scripts with a schedule and remove the rest, through systemd
+/// if it is running and cron otherwise
+pub fn apply_schedules(ctx: &Context) -> Result<(), String> {
+    let scheduled = scheduled_scripts(&ctx.conn);
+    let nym = std::env::current_exe().map_err(|err| format!("Could not find nym: {}", err))?;
+    let path_var = std::env::var("PATH").unwrap_or_default();
+    let found = |command: &str| find_executable(command, &path_var, ctx.nym_dir()).is_some();
+
+    // The same check systemd's own tools use to tell whether it is running
+    if Path::new("/run/systemd/system").is_dir() && found("systemctl") {
+        let unit_dir = systemd_unit_dir().ok_or("Could not find the config directory")?;
+        sync_systemd(&unit_dir, &scheduled, &nym, run_systemctl)
+    } else if found("crontab") {
+        sync_cron(&scheduled, &nym)
+    } else if scheduled.is_empty() {
+        Ok(())
+    } else {
+        Err("Neither systemd nor cron is available to run scheduled scripts".to_string())
+    }
+}
+
+/// Run a script on a schedule, or stop running it with `remove`
+///
+/// Without a spec the current schedule is shown.
+pub fn schedule_script(ctx: &Context, name: &str, spec: Option<&str>, remove: bool) {
+    let script = match get_script(ctx, name) {
+        Ok(script) => script,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let schedule = match (spec, remove) {
+        (_, true) => None,
+        (Some(spec), false) => {
+            if let Err(err) = calendar_to_cron(spec) {
+                error!(err);
+                return;
+            }
+            Some(spec.trim().to_string())
+        }
+        (None, false) => {
+            match &script.schedule {
+                Some(schedule) => println!("{}", schedule),
+                None => warning!(format!("{} is not scheduled", style(&script.name).bold())),
+            }
+            return;
+        }
+    };
+
+    let updated = crate::file_management::Script {
+        schedule: schedule.clone(),
+        ..script.clone()
+    };
+    if let Err(err) =
+        ctx.commit(|tx| update_script(tx, &script.name, &updated).map_err(String::from))
+    {
+        error!(format!("Could not update script: {}", err));
+        return;
+    }
+    if let Err(err) = apply_schedules(ctx) {
+        error!(err);
+        return;
+    }
+
+    match schedule {
+        Some(schedule) => {
+            success!(format!(
+                "Script {} will run on {}",
+                style(&script.name).bold(),
+                style(schedule).bold()
+            ));
+            if !script.enabled {
+                warning!("The script is disabled, it will start running once it is enabled");
+            }
+        }
+        None => success!(format!(
+            "Script {} is no longer scheduled",
+            style(&script.name).bold()
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    #[test]
+    fn calendar_to_cron_test() {
+        let cron = |spec: &str| calendar_to_cron(spec);
+        assert_eq!(cron("daily"), Ok("0 0 * * *".to_string()));
+        assert_eq!(cron("Mon..Fri 09:30"), Ok("30 9 * * 1,2,3,4,5".to_string()));
+        assert_eq!(cron("Sat..Sun 8:00:00"), Ok("0 8 * * 6,0".to_string()));
+        assert_eq!(cron("*-*-01 *:0/15"), Ok("0-59/15 * 1 * *".to_string()));
+        assert_eq!(cron("1,7-1 18..20:00"), Ok("0 18-20 1 1,7 *".to_string()));
+        assert_eq!(cron("Wed"), Ok("0 0 * * 3".to_string()));
+
+        assert!(cron("2027-01-01 10:00").is_err());
+        assert!(cron("10:00:30").is_err());
+        assert!(cron("25:00").is_err());
+        assert!(cron("Funday 10:00").is_err());
+        assert!(cron("Mon *-*-01").is_err());
+        assert_eq!(cron("Mon *-1-*"), Ok("0 0 * 1 1".to_string()));
+        assert!(cron("10:00 extra").is_err());
+        assert!(cron("").is_err());
+    }
+
+    #[test]
+    fn sync_systemd_test() {
+        let unit_dir = Path::new("sync_systemd_test");
+        let nym = Path::new("/usr/bin/nym");
+        let calls = RefCell::new(Vec::new());
+        let systemctl = |args: &[&str]| {
+            calls.borrow_mut().push(args.join(" "));
+            Ok(())
+        };
+        let scheduled = |specs: &[(&str, &str)]| {
+            specs
+                .iter()
+                .map(|(name, spec)| (name.to_string(), spec.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        sync_systemd(
+            unit_dir,
+            &scheduled(&[("backup", "daily"), ("report", "Mon 09:00")]),
+            nym,
+            systemctl,
+        )
+        .unwrap();
+        let timer = std::fs::read_to_string(unit_dir.join("nym-backup.timer")).unwrap();
+        assert!(timer.contains("OnCalendar=daily\n"));
+        let service = std::fs::read_to_string(unit_dir.join("nym-report.service")).unwrap();
+        assert!(service.contains("ExecStart=\"/usr/bin/nym\" exec \"report\"\n"));
+
+        // Quotes, `%` specifiers and `$` variables in names are escaped
+        assert_eq!(unit_name("50% off"), r"nym-50\x25\x20off");
+        let service = service_unit("50% \"off\" $HOME", Path::new("/opt/my tools/nym"));
+        assert!(service.contains("Description=nym script 50%% \"off\" $HOME\n"));
+        assert!(service.contains(r#"ExecStart="/opt/my tools/nym" exec "50%% \"off\" $$HOME""#));
+        assert_eq!(
+            calls.take(),
+            vec![
+                "daemon-reload",
+                "enable nym-backup.timer",
+                "restart nym-backup.timer",
+                "enable nym-report.timer",
+                "restart nym-report.timer",
+            ]
+        );
+
+        // Nothing changed, so systemd is left alone
+        std::fs::write(unit_dir.join("other.timer"), "").unwrap();
+        sync_systemd(
+            unit_dir,
+            &scheduled(&[("backup", "daily"), ("report", "Mon 09:00")]),
+            nym,
+            systemctl,
+        )
+        .unwrap();
+        assert!(calls.borrow().is_empty());
+
+        // Timers that go are disabled before their files are deleted, units that aren't nym's stay
+        sync_systemd(
+            unit_dir,
+            &scheduled(&[("backup", "hourly")]),
+            nym,
+            systemctl,
+        )
+        .unwrap();
+        assert_eq!(
+            calls.take(),
+            vec![
+                "disable --now nym-report.timer",
+                "daemon-reload",
+                "enable nym-backup.timer",
+                "restart nym-backup.timer",
+            ]
+        );
+        assert!(!unit_dir.join("nym-report.service").exists());
+        assert!(unit_dir.join("other.timer").exists());
+
+        std::fs::remove_dir_all(unit_dir).expect("Error cleaning up test files");
+    }
+
+    #[test]
+    fn replace_cron_block_test() {
+        let nym = Path::new("/opt/my tools/nym");
+        let scheduled = vec![("backup".to_string(), "Mon..Fri 02:30".to_string())];
+        let crontab = replace_cron_block("MAILTO=me\n0 1 * * * other\n", &scheduled, nym).unwrap();
+        assert_eq!(
+            crontab,
+            "MAILTO=me\n0 1 * * * other\n# BEGIN nym schedules\n\
+            30 2 * * 1,2,3,4,5 '/opt/my tools/nym' exec 'backup'\n# END nym schedules\n"
+        );
+
+        // The name is one word for sh and a `%` isn't turned into a newline by cron
+        let odd = vec![("50% off; rm".to_string(), "hourly".to_string())];
+        assert_eq!(
+            replace_cron_block("", &odd, nym).unwrap(),
+            "# BEGIN nym schedules\n\
+            0 * * * * '/opt/my tools/nym' exec '50\\% off; rm'\n# END nym schedules\n"
+        );
+
+        // The block is replaced, not added to, and goes once nothing is scheduled
+        assert_eq!(
+            replace_cron_block(&crontab, &scheduled, nym).unwrap(),
+            crontab
+        );
+        assert_eq!(
+            replace_cron_block(&crontab, &[], nym).unwrap(),
+            "MAILTO=me\n0 1 * * * other\n"
+        );
+    }
+}
@@ -0,0 +1,200 @@
+use std::path::Path;
+
+use console::style;
+use rusqlite::Connection;
+
+use crate::{
+    commands::Context,
+    error,
+    file_management::{
+        database::{script_versions::get_script_versions, scripts::get_all_scripts},
+        Script,
+    },
+    info, success,
+};
+
+use super::{
+    check::FixIssues,
+    edit::get_script,
+    history::{current_hash, hash_content},
+    update::update_script,
+};
+
+/// How a copied script has drifted from the file it was added from
+#[derive(Debug, PartialEq)]
+pub enum Drift {
+    /// The source was changed after the script was last copied from it
+    SourceChanged,
+    /// The stored copy was edited since nym last wrote it
+    CopyEdited,
+    /// The source no longer exists
+    SourceMissing,
+}
+
+impl Drift {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            Drift::SourceChanged => "source changed",
+            Drift::CopyEdited => "copy edited",
+            Drift::SourceMissing => "source is gone",
+        }
+    }
+}
+
+/// Ways `script` has drifted from its source
+///
+/// Only copied single file scripts are tracked, a linked script is its source and projects
+/// aren't hashed.
+pub fn check_drift(conn: &Connection, script: &Script) -> Vec<Drift> {
+    let source = match &script.source_path {
+        Some(source) if !script.linked && script.entry_point.is_none() => Path::new(source),
+        _ => return Vec::new(),
+    };
+
+    let mut drift = Vec::new();
+    let copy_hash = current_hash(script);
+    match std::fs::read(source) {
+        // Scripts added before hashes were kept compare with the copy instead
+        Ok(content) => {
+            if Some(hash_content(&content)) != script.source_hash.clone().or(copy_hash.clone()) {
+                drift.push(Drift::SourceChanged);
+            }
+        }
+        Err(_) => drift.push(Drift::SourceMissing),
+    }
+
+    // The last version is what nym last wrote to the copy
+    if let Some(version) = get_script_versions(conn, &script.name).last() {
+        if copy_hash.is_some_and(|hash| hash != version.hash) {
+            drift.push(Drift::CopyEdited);
+        }
+    }
+    drift
+}
+
+/// List the scripts that have drifted from their sources
+pub fn script_status(ctx: &Context) {
+    let mut drifted = false;
+
+    for script in get_all_scripts(&ctx.conn) {
+        let drift = check_drift(&ctx.conn, &script);
+        if drift.is_empty() {
+            continue;
+        }
+        drifted = true;
+        println!(
+            "{} {}: {}",
+            style(&script.name).bold(),
+            style(script.source_path.as_deref().unwrap_or_default()).dim(),
+            drift
+                .iter()
+                .map(|drift| style(drift.describe()).yellow().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    if !drifted {
+        success!("All scripts match their sources");
+    }
+}
+
+/// Pull changes from the source of the script called `name`, or of every script whose source
+/// changed
+///
+/// Each script shows a diff and needs confirming unless `yes` is set. Edits to the copy are
+/// replaced, though they can still be found in the script's history.
+pub fn sync_scripts(ctx: &Context, name: Option<&str>, yes: bool) {
+    let scripts = match name {
+        Some(name) => match get_script(ctx, name) {
+            Ok(script) => vec![script],
+            Err(err) => {
+                error!(err);
+                return;
+            }
+        },
+        None => get_all_scripts(&ctx.conn),
+    };
+
+    let mut synced = false;
+    for script in scripts {
+        let drift = check_drift(&ctx.conn, &script);
+        if drift.contains(&Drift::SourceMissing) && name.is_some() {
+            error!(format!(
+                "The source of {} is gone",
+                style(&script.name).bold()
+            ));
+        }
+        if !drift.contains(&Drift::SourceChanged) {
+            continue;
+        }
+
+        synced = true;
+        if drift.contains(&Drift::CopyEdited) {
+            info!(format!(
+                "The copy of {} was edited too, run {} to get the edits back",
+                style(&script.name).bold(),
+                style(format!("`nym script history {}`", script.name)).bold()
+            ));
+        }
+        let message = format!(
+            "Synced from {}",
+            script.source_path.as_deref().unwrap_or_default()
+        );
+        let fix = if yes {
+            FixIssues::Never
+        } else {
+            FixIssues::Ask
+        };
+        update_script(ctx, &script.name, None, Some(&message), yes, fix);
+    }
+
+    if !synced {
+        info!("No sources have changed");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        commands::scripts::add::add_script, file_management::database::scripts::get_script_by_name,
+    };
+
+    use super::*;
+
+    #[test]
+    fn script_drift_test() {
+        let test_dir = Path::new("script_drift_test");
+        let db_file = "script_drift_test/nym.db";
+        let rc_file = "script_drift_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        let source = test_dir.join("drift.sh");
+        std::fs::write(&source, "#!/bin/sh\necho one\n").unwrap();
+        let source = source.to_str().unwrap();
+        add_script(&ctx, source, "", None, false, false, FixIssues::Never);
+        let script = get_script_by_name(&ctx.conn, "drift").unwrap();
+        assert_eq!(check_drift(&ctx.conn, &script), vec![]);
+
+        std::fs::write(source, "#!/bin/sh\necho two\n").unwrap();
+        std::fs::write(&script.path, "#!/bin/sh\necho edited\n").unwrap();
+        assert_eq!(
+            check_drift(&ctx.conn, &script),
+            vec![Drift::SourceChanged, Drift::CopyEdited]
+        );
+
+        sync_scripts(&ctx, Some("drift"), true);
+        let script = get_script_by_name(&ctx.conn, "drift").unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&script.path).unwrap(),
+            "#!/bin/sh\necho two\n"
+        );
+        assert_eq!(check_drift(&ctx.conn, &script), vec![]);
+
+        std::fs::remove_file(source).unwrap();
+        assert_eq!(check_drift(&ctx.conn, &script), vec![Drift::SourceMissing]);
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
@@ -0,0 +1,290 @@
+use std::path::{Path, PathBuf};
+
+use console::style;
+
+use super::{
+    add::{register_script, script_entry},
+    check::{review_issues, FixIssues},
+};
+use crate::{
+    commands::{collisions::check_script_name, groups::ask_fuzzy_get, Context},
+    error,
+    helpers::editor::edit_text,
+    success,
+};
+
+const PYTHON_TEMPLATE: &str = r#"#!/usr/bin/env python3
+"""{{description}}
+
+Usage: {{name}} [-h] [args ...]
+Group: {{group}}
+"""
+import argparse
+
+
+def main():
+    parser = argparse.ArgumentParser(prog="{{name}}", description=__doc__.splitlines()[0])
+    parser.add_argument("args", nargs="*")
+    args = parser.parse_args()
+
+
+if __name__ == "__main__":
+    main()
+"#;
+
+const BASH_TEMPLATE: &str = r#"#!/usr/bin/env bash
+# Description: {{description}}
+# Usage: {{name}} [-h] [args...]
+# Group: {{group}}
+set -euo pipefail
+
+usage() {
+    echo "Usage: {{name}} [-h] [args...]"
+}
+
+while getopts "h" opt; do
+    case "$opt" in
+        h) usage; exit 0 ;;
+        *) usage >&2; exit 1 ;;
+    esac
+done
+shift $((OPTIND - 1))
+"#;
+
+const NODE_TEMPLATE: &str = r#"#!/usr/bin/env node
+// Description: {{description}}
+// Usage: {{name}} [-h] [args...]
+// Group: {{group}}
+const { parseArgs } = require("node:util");
+
+const { values, positionals } = parseArgs({
+    options: { help: { type: "boolean", short: "h" } },
+    allowPositionals: true,
+});
+
+if (values.help) {
+    console.log("Usage: {{name}} [-h] [args...]");
+    process.exit(0);
+}
+"#;
+
+/// Languages `nym new script` has templates for
+pub const LANGUAGES: [&str; 3] = ["python", "bash", "node"];
+
+/// Extension of scripts in `lang` and the template used when there isn't one in the templates
+/// directory
+fn language(lang: &str) -> Option<(&'static str, &'static str)> {
+    match lang {
+        "python" => Some(("py", PYTHON_TEMPLATE)),
+        "bash" => Some(("sh", BASH_TEMPLATE)),
+        "node" => Some(("js", NODE_TEMPLATE)),
+        _ => None,
+    }
+}
+
+/// Template file for `lang`, e.g. `templates/python.py`
+fn template_path(ctx: &Context, lang: &str, extension: &str) -> PathBuf {
+    ctx.nym_dir()
+        .join("templates")
+        .join(format!("{}.{}", lang, extension))
+}
+
+/// Read the template for `lang`, writing the default one first so it can be customised
+fn load_template(path: &Path, default: &str) -> Result<String, String> {
+    if !path.exists() {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|err| format!("Could not create templates directory: {}", err))?;
+        }
+        std::fs::write(path, default)
+            .map_err(|err| format!("Could not write {}: {}", path.display(), err))?;
+    }
+    std::fs::read_to_string(path)
+        .map_err(|err| format!("Could not read {}: {}", path.display(), err))
+}
+
+/// Fill in the `{{name}}`, `{{description}}` and `{{group}}` placeholders of a template
+fn render_template(template: &str, name: &str, description: &str, group: &str) -> String {
+    template
+        .replace("{{name}}", name)
+        .replace("{{description}}", description)
+        .replace("{{group}}", group)
+}
+
+/// Create the script `name` from the template for `lang`, edit it in `$EDITOR` and add it
+///
+/// The description and group are written into the script's header, so changes made to them in
+/// the editor are kept.
+pub fn new_script(
+    ctx: &Context,
+    name: &str,
+    lang: &str,
+    description: &str,
+    group_name: Option<&str>,
+    strict: bool,
+) {
+    let (extension, default) = match language(lang) {
+        Some(language) => language,
+        None => {
+            error!(format!("There is no template for {}", lang));
+            return;
+        }
+    };
+    if !check_script_name(ctx, name, strict) {
+        return;
+    }
+    let group = match group_name.map(|group_name| ask_fuzzy_get(&ctx.conn, group_name)) {
+        Some(Some(group)) => Some(group),
+        Some(None) => {
+            error!(format!(
+                "Could not find group {}",
+                style(group_name.unwrap_or_default()).bold()
+            ));
+            return;
+        }
+        None => None,
+    };
+
+    let template = match load_template(&template_path(ctx, lang, extension), default) {
+        Ok(template) => template,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+    let file_name = format!("{}.{}", name, extension);
+    let group_header = group.as_ref().map_or("", |group| group.name.as_str());
+    let content = match edit_text(
+        &render_template(&template, name, description, group_header),
+        &file_name,
+    ) {
+        Ok(content) if content.trim().is_empty() => {
+            error!("The script is empty, not adding it");
+            return;
+        }
+        Ok(content) => content,
+        Err(err) => {
+            error!(err);
+            return;
+        }
+    };
+
+    let script_dir = ctx.scripts_dir().join(name);
+    if let Err(err) = std::fs::create_dir_all(&script_dir) {
+        error!(format!("Issue creating script directory: {}", err));
+        return;
+    }
+    let stored_path = script_dir.join(&file_name);
+    let written = std::fs::write(&stored_path, content).and_then(|_| {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(&stored_path, std::fs::Permissions::from_mode(0o755))?;
+        }
+        Ok(())
+    });
+    if let Err(err) = written {
+        error!(format!("Issue writing script: {}", err));
+        let _ = std::fs::remove_dir_all(&script_dir);
+        return;
+    }
+
+    // The header holds the description and group as edited
+    let mut script = script_entry(ctx, name, &stored_path, "", group.map(|group| group.id));
+    script.issues = review_issues(&script, &stored_path, FixIssues::Always);
+
+    let message = format!("Created from the {} template", lang);
+    if let Err(err) = register_script(ctx, &script, &message, None) {
+        error!(format!("Issue adding script: {}", err));
+        return;
+    }
+    success!(format!(
+        "Script {} created at {}",
+        style(name).bold(),
+        stored_path.display()
+    ));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        commands::{groups::add::add_group, scripts::header::parse_header},
+        file_management::{bin_dir::bin_dir, database::scripts::get_script_by_name},
+    };
+
+    #[test]
+    fn template_test() {
+        let test_dir = Path::new("template_test");
+        let template = test_dir.join("templates/bash.sh");
+
+        // The default is written out to be customised, then the file is used
+        assert_eq!(
+            load_template(&template, BASH_TEMPLATE).unwrap(),
+            BASH_TEMPLATE
+        );
+        std::fs::write(&template, "#!/bin/sh\n# Description: {{description}}\n").unwrap();
+        assert_eq!(
+            load_template(&template, BASH_TEMPLATE).unwrap(),
+            "#!/bin/sh\n# Description: {{description}}\n"
+        );
+
+        // Every default template has a header nym reads back
+        for lang in LANGUAGES {
+            let (_, default) = language(lang).unwrap();
+            let header = parse_header(&render_template(default, "tool", "Does things", "work"));
+            assert_eq!(
+                header.description.as_deref(),
+                Some("Does things"),
+                "{}",
+                lang
+            );
+            assert_eq!(header.group.as_deref(), Some("work"), "{}", lang);
+            assert!(header.usage.unwrap().starts_with("tool "), "{}", lang);
+        }
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+
+    #[test]
+    fn new_script_test() {
+        let test_dir = "new_script_test";
+        let db_file = "new_script_test/nym.db";
+        let rc_file = "new_script_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+        add_group(&ctx, "work");
+
+        // An editor that leaves the template as it is
+        std::env::set_var("VISUAL", "true");
+        new_script(&ctx, "tool", "bash", "Does things", Some("work"), false);
+
+        let script = get_script_by_name(&ctx.conn, "tool").unwrap();
+        assert_eq!(script.path, "new_script_test/scripts/tool/tool.sh");
+        assert_eq!(script.description, "Does things");
+        assert_eq!(script.group_id, 2);
+        assert!(script.issues.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(&script.path).unwrap(),
+            render_template(BASH_TEMPLATE, "tool", "Does things", "work")
+        );
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&script.path)
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+
+        // It can be run from PATH straight away
+        let entry = bin_dir(Path::new(rc_file)).join("tool.sh");
+        assert_eq!(
+            std::fs::canonicalize(entry).unwrap(),
+            std::fs::canonicalize(&script.path).unwrap()
+        );
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
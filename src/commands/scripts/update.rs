@@ -1,87 +1,365 @@
+use std::path::Path;
+
+use console::style;
+
+use super::{
+    check::{review_issues, FixIssues},
+    header::read_header,
+    history::{hash_content, record_version, version_path},
+    interpreter::detect_script_type,
+    launcher::{install_requirements, write_launcher},
+    project::update_project,
+};
 use crate::{
+    commands::Context,
     error,
-    file_management::database::{
-            db_conn,
-            scripts::get_script_by_name,
+    file_management::{
+        database::{
+            script_versions::get_script_versions,
+            scripts::{get_script_by_name, update_script as update_script_in_database},
         },
-    helpers::questions::get_filepath,
-    success,
+        Script,
+    },
+    helpers::{
+        diff::print_unified_diff,
+        questions::{get_filepath, yesno},
+    },
+    info, success,
 };
 
-#[allow(dead_code, unreachable_code, unused_variables)]
-pub fn update_script(db_file: &str, rc_file: &str, script_name: &str, update_path: Option<&str>) {
-    // TODO: Add execution check - if not executable add option to change permissions
-    todo!();
-    let script_path = match update_path {
-        Some(str) => str,
-        None => &get_filepath!("Updated script filepath").unwrap(),
+/// Give `path` the execute bits `reference` has, so replacing a script never makes it
+/// unexecutable
+#[cfg(unix)]
+fn keep_executable_bit(reference: &Path, path: &Path) -> std::io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let reference_mode = match std::fs::metadata(reference) {
+        Ok(metadata) => metadata.permissions().mode(),
+        Err(_) => return Ok(()),
     };
+    let mut permissions = std::fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | (reference_mode & 0o111));
+    std::fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn keep_executable_bit(_reference: &Path, _path: &Path) -> std::io::Result<()> {
+    Ok(())
+}
 
-    let conn = db_conn(db_file);
-    // Check if script exists
-    if get_script_by_name(&conn, script_name).is_err() {
-        error!("Script does not exists");
+/// Replace the file of `script` with a copy of `source` stored at `new_path`, saving it as a new
+/// version with `message`
+///
+/// `new_source` is the path and hash of the file that becomes the script's source, `None` keeps
+/// the current source, e.g. when rolling back.
+///
+/// Issues with the new file are fixed as `fix` says. Scripts added before versions were kept get their current file saved first.
+pub(crate) fn replace_script(
+    ctx: &Context,
+    script: &Script,
+    source: &Path,
+    new_path: &Path,
+    new_source: Option<(String, String)>,
+    message: &str,
+    fix: FixIssues,
+) -> Result<(), String> {
+    let current_path = Path::new(&script.path);
+
+    // Copy next to the stored script first so a failed copy leaves it untouched
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(new_path.file_name().unwrap_or_default());
+    tmp_name.push(".tmp");
+    let tmp_path = new_path.with_file_name(tmp_name);
+
+    let copied =
+        std::fs::copy(source, &tmp_path).and_then(|_| keep_executable_bit(current_path, &tmp_path));
+    if let Err(err) = copied {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(format!("Could not copy {}: {}", source.display(), err));
+    }
+
+    let (source_path, source_hash) = match new_source {
+        Some((path, hash)) => (Some(path), Some(hash)),
+        None => (script.source_path.clone(), script.source_hash.clone()),
+    };
+    let mut updated = Script {
+        path: new_path.to_str().unwrap().to_string(),
+        source_path,
+        source_hash,
+        type_: detect_script_type(source),
+        usage: read_header(source).usage.unwrap_or_default(),
+        ..script.clone()
+    };
+    updated.issues = review_issues(&updated, &tmp_path, fix);
+
+    let mut saved_versions = Vec::new();
+    let result = ctx.commit(|tx| {
+        if get_script_versions(tx, &script.name).is_empty() && current_path.is_file() {
+            let version = record_version(tx, script, current_path, "Saved before updating")?;
+            saved_versions.push(version_path(script, &version));
+        }
+        update_script_in_database(tx, &script.name, &updated)?;
+        let version = record_version(tx, &updated, &tmp_path, message)?;
+        saved_versions.push(version_path(&updated, &version));
+        // Written before the bin directory so its entry runs the new launcher
+        write_launcher(ctx, &updated)
+    });
+    if result.is_err() {
+        // The database was rolled back, so the files saved for it go and the launcher is put back
+        let _ = std::fs::remove_file(&tmp_path);
+        for path in saved_versions {
+            let _ = std::fs::remove_file(path);
+        }
+        let _ = write_launcher(ctx, script);
+        return result;
+    }
+
+    // The live file is only replaced once the change is saved
+    if let Err(err) = std::fs::rename(&tmp_path, new_path) {
+        let _ = std::fs::remove_file(&tmp_path);
+        return Err(format!(
+            "Could not replace script, the new version is saved and can be rolled back to: {}",
+            err
+        ));
+    }
+
+    if new_path != current_path {
+        let _ = std::fs::remove_file(current_path);
+    }
+    Ok(())
+}
+
+/// Replace the stored copy of a script with the file at `update_path`, the path it was imported
+/// from or a path asked for, in that order
+///
+/// A unified diff is shown first and the replacement needs confirming unless `yes` is set. The
+/// new file is saved as a version with `message`, or a note of where it came from, after its
+/// issues are fixed as `fix` says.
+pub fn update_script(
+    ctx: &Context,
+    script_name: &str,
+    update_path: Option<&str>,
+    message: Option<&str>,
+    yes: bool,
+    fix: FixIssues,
+) {
+    let script = match get_script_by_name(&ctx.conn, script_name) {
+        Ok(script) => script,
+        Err(_) => {
+            error!("Script does not exists");
+            return;
+        }
+    };
+    if script.entry_point.is_some() {
+        update_project(ctx, &script, update_path, yes);
+        return;
+    }
+    if script.linked {
+        error!(format!(
+            "Script {} is a link to {}, edit that file instead",
+            style(&script.name).bold(),
+            script.source_path.as_deref().unwrap_or_default()
+        ));
         return;
     }
 
-    // get script name from path
-    let file_name = script_path
-        .split('/')
-        .collect::<Vec<&str>>()
-        .last()
-        .unwrap()
-        .to_string();
-
-    let db_path = std::path::Path::new(db_file);
-    let parent_dir = db_path.parent().unwrap();
-
-    let scripts_dir = parent_dir.join("scripts");
-    let script_name_no_ext = script_name.split('.').collect::<Vec<&str>>()[0];
-    if !scripts_dir.exists() && std::fs::create_dir(&scripts_dir).is_err() {
-        error!("Issue creating scripts directory");
+    let recorded_source = script
+        .source_path
+        .clone()
+        .filter(|path| Path::new(path).is_file());
+    let source = match (update_path, recorded_source) {
+        (Some(path), _) => path.to_string(),
+        (None, Some(path)) => {
+            info!(format!("Updating from {}", style(&path).bold()));
+            path
+        }
+        (None, None) => match get_filepath!("Updated script filepath") {
+            Ok(path) => path,
+            Err(_) => return,
+        },
+    };
+    let source_path = Path::new(&source);
+
+    let new_content = match std::fs::read(source_path) {
+        Ok(content) => content,
+        Err(err) => {
+            error!(format!("Could not read {}: {}", source, err));
+            return;
+        }
+    };
+    let old_content = std::fs::read(&script.path).unwrap_or_default();
+
+    // The stored file takes the extension of the new one
+    let current_path = Path::new(&script.path);
+    let new_path = current_path.with_extension(source_path.extension().unwrap_or_default());
+
+    if old_content == new_content && new_path == current_path {
+        info!(format!(
+            "Script {} is already up to date",
+            style(&script.name).bold()
+        ));
+        return;
+    }
+
+    print_unified_diff(
+        &String::from_utf8_lossy(&old_content),
+        &String::from_utf8_lossy(&new_content),
+        &script.path,
+        &source,
+    );
+    if new_path != current_path {
+        info!(format!("Script will be stored as {}", new_path.display()));
+    }
+
+    if !yes && !yesno!("Replace the script with these changes?").unwrap_or(false) {
+        println!("{}", style("Aborting").yellow());
+        return;
+    }
+
+    let canonical_source = std::fs::canonicalize(source_path)
+        .ok()
+        .and_then(|path| path.to_str().map(String::from));
+    let message = match message {
+        Some(message) => message.to_string(),
+        None => format!(
+            "Updated from {}",
+            canonical_source.as_deref().unwrap_or(&source)
+        ),
+    };
+
+    let result = replace_script(
+        ctx,
+        &script,
+        source_path,
+        &new_path,
+        // The new file becomes the script's source
+        canonical_source.map(|path| (path, hash_content(&new_content))),
+        &message,
+        fix,
+    );
+    if let Err(err) = result {
+        error!(format!("Could not update script: {}", err));
         return;
     }
 
-    // Create folder in scripts directory
-    // if std::fs::create_dir(scripts_dir.join(script_name_no_ext)).is_err() {
-    //     error!("Issue creating script directory");
-    //     return;
-    // }
-
-    // Copy script to scripts directory
-    if std::fs::copy(
-        script_path,
-        scripts_dir
-            .join(script_name_no_ext)
-            .join(script_name),
-    )
-    .is_err()
-    {
-        error!("Issue copying script to scripts directory");
+    // The requirements next to the new file may have changed, they are only installed once the
+    // update is saved so an update that fails leaves the venv alone
+    let installed = get_script_by_name(&ctx.conn, &script.name)
+        .map_err(String::from)
+        .and_then(|updated| {
+            ctx.commit(|_| {
+                install_requirements(ctx, &updated, source_path.parent())?;
+                // A new venv needs a launcher to run the script in it
+                write_launcher(ctx, &updated)
+            })
+        });
+    if let Err(err) = installed {
+        error!(format!(
+            "Script {} updated, but its requirements could not be installed: {}",
+            style(&script.name).bold(),
+            err
+        ));
         return;
     }
+    success!(format!("Script {} updated", style(&script.name).bold()));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands::scripts::{add::add_script, history::versions_dir};
+
+    use super::*;
 
-    // let script = Script {
-    //     name: script_name_no_ext.to_string(),
-    //     path: scripts_dir
-    //         .join(script_name_no_ext)
-    //         .join(script_name.clone())
-    //         .to_str()
-    //         .unwrap()
-    //         .to_string(),
-    //     description: description.to_string(),
-    //     enabled: true,
-    //     group_id,
-    // };
-
-    // Add script to database
-    // if add_script_to_database(&conn, &script).is_err() {
-    //     error!("Issue adding script to database");
-    //     return;
-    // }
-
-    // Update runcom file
-    // update_runcom(rc_file, db_file);
-    success!("Script added successfully");
-    todo!()
+    #[test]
+    fn update_script_test() {
+        let test_dir = "update_script_test";
+        let db_file = "update_script_test/nym.db";
+        let rc_file = "update_script_test/nymrc";
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+        let ctx = Context::new(rc_file, db_file).unwrap();
+
+        let source = "update_script_test/update_test_script.sh";
+        std::fs::write(source, "echo one\n").unwrap();
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(source, std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        add_script(&ctx, source, "", None, false, false, FixIssues::Never);
+        let script = get_script_by_name(&ctx.conn, "update_test_script").unwrap();
+        assert!(script.source_path.unwrap().ends_with(source));
+
+        // Defaults to the recorded source
+        std::fs::write(source, "echo two\n").unwrap();
+        update_script(
+            &ctx,
+            "update_test_script",
+            None,
+            None,
+            true,
+            FixIssues::Never,
+        );
+        let script = get_script_by_name(&ctx.conn, "update_test_script").unwrap();
+        assert_eq!(std::fs::read_to_string(&script.path).unwrap(), "echo two\n");
+
+        // A new extension renames the stored copy, the execute bit is kept
+        let python = "update_script_test/new_version.py";
+        std::fs::write(python, "print('three')\n").unwrap();
+        update_script(
+            &ctx,
+            "update_test_script",
+            Some(python),
+            None,
+            true,
+            FixIssues::Never,
+        );
+        let updated = get_script_by_name(&ctx.conn, "update_test_script").unwrap();
+        assert_eq!(
+            updated.path,
+            "update_script_test/scripts/update_test_script/update_test_script.py"
+        );
+        assert_eq!(
+            std::fs::read_to_string(&updated.path).unwrap(),
+            "print('three')\n"
+        );
+        assert!(!Path::new(&script.path).exists());
+        assert!(updated.source_path.clone().unwrap().ends_with(python));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&updated.path)
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+
+        // When the runcom can't be written nothing changes, on disk or in the database
+        std::fs::remove_file(rc_file).unwrap();
+        std::fs::create_dir(rc_file).unwrap();
+        std::fs::write(python, "print('four')\n").unwrap();
+        update_script(
+            &ctx,
+            "update_test_script",
+            Some(python),
+            None,
+            true,
+            FixIssues::Never,
+        );
+        assert_eq!(
+            get_script_by_name(&ctx.conn, "update_test_script").unwrap(),
+            updated
+        );
+        assert_eq!(
+            std::fs::read_to_string(&updated.path).unwrap(),
+            "print('three')\n"
+        );
+        let saved = std::fs::read_dir(versions_dir(&updated)).unwrap().count();
+        assert_eq!(
+            saved,
+            get_script_versions(&ctx.conn, "update_test_script").len()
+        );
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
 }
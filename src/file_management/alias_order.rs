@@ -0,0 +1,204 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+use super::Alias;
+
+/// Words that introduce a command without being one themselves
+const SHELL_PREFIXES: &[&str] = &[
+    "!", "if", "then", "else", "elif", "do", "while", "until", "time",
+];
+
+/// Words in command position in a shell command line, i.e. the words the shell would try to
+/// expand as an alias
+pub fn command_words(command: &str) -> Vec<&str> {
+    let separators = ['|', '&', ';', '(', ')', '`', '{', '}', '\n'];
+
+    command
+        .split(|c| separators.contains(&c))
+        .filter_map(|segment| {
+            segment
+                .split_whitespace()
+                .find(|word| !SHELL_PREFIXES.contains(word) && !is_assignment(word))
+        })
+        .collect()
+}
+
+/// `NAME=value` prefixes set the environment for a command, they are not the command
+fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            !name.is_empty()
+                && !name.starts_with(|c: char| c.is_ascii_digit())
+                && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// For every alias, the indices of the aliases that have to be defined before it: the aliases
+/// its command calls and its explicit `after`.
+///
+/// Aliases referring to themselves (`ls='ls --color'`) call the real command, not the alias, so
+/// they don't depend on anything.
+fn dependencies(aliases: &[&Alias]) -> Vec<Vec<usize>> {
+    let index: HashMap<&str, usize> = aliases
+        .iter()
+        .enumerate()
+        .map(|(i, alias)| (alias.name.as_str(), i))
+        .collect();
+
+    aliases
+        .iter()
+        .enumerate()
+        .map(|(i, alias)| {
+            let mut deps: Vec<usize> = command_words(&alias.command)
+                .into_iter()
+                .chain(alias.after.as_deref())
+                .filter_map(|name| index.get(name).copied())
+                .filter(|&dep| dep != i)
+                .collect();
+            deps.sort();
+            deps.dedup();
+            deps
+        })
+        .collect()
+}
+
+/// Order in which aliases have to be written so every alias is defined after the aliases it
+/// depends on.
+///
+/// Returns indices into `aliases`. Aliases that don't depend on each other keep their original
+/// order, and any aliases caught in a cycle are appended in their original order.
+pub fn load_order(aliases: &[&Alias]) -> Vec<usize> {
+    let deps = dependencies(aliases);
+
+    let mut remaining: Vec<usize> = deps.iter().map(|d| d.len()).collect();
+    let mut dependents = vec![Vec::new(); aliases.len()];
+    for (i, alias_deps) in deps.iter().enumerate() {
+        for &dep in alias_deps {
+            dependents[dep].push(i);
+        }
+    }
+
+    let mut ready: BinaryHeap<Reverse<usize>> = remaining
+        .iter()
+        .enumerate()
+        .filter(|(_, &count)| count == 0)
+        .map(|(i, _)| Reverse(i))
+        .collect();
+
+    let mut order = Vec::with_capacity(aliases.len());
+    while let Some(Reverse(i)) = ready.pop() {
+        order.push(i);
+        for &dependent in &dependents[i] {
+            remaining[dependent] -= 1;
+            if remaining[dependent] == 0 {
+                ready.push(Reverse(dependent));
+            }
+        }
+    }
+
+    if order.len() < aliases.len() {
+        let mut cyclic: Vec<usize> = (0..aliases.len()).filter(|i| remaining[*i] > 0).collect();
+        order.append(&mut cyclic);
+    }
+    order
+}
+
+/// Find a dependency cycle running through the alias at `start`, as the list of alias names
+/// along the cycle starting and ending with `start`
+pub fn find_cycle(aliases: &[&Alias], start: usize) -> Option<Vec<String>> {
+    let deps = dependencies(aliases);
+
+    let mut visited = vec![false; aliases.len()];
+    let mut path = vec![start];
+    // Each stack entry is a node on the path and the next of its dependencies to visit
+    let mut stack = vec![(start, 0)];
+
+    while let Some((node, next)) = stack.pop() {
+        match deps[node].get(next) {
+            Some(&dep) => {
+                stack.push((node, next + 1));
+                if dep == start {
+                    path.push(start);
+                    return Some(path.iter().map(|&i| aliases[i].name.clone()).collect());
+                }
+                if !visited[dep] {
+                    visited[dep] = true;
+                    path.push(dep);
+                    stack.push((dep, 0));
+                }
+            }
+            None => {
+                path.pop();
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn alias(name: &str, command: &str, after: Option<&str>) -> Alias {
+        Alias {
+            name: name.to_string(),
+            command: command.to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id: 1,
+            after: after.map(|a| a.to_string()),
+        }
+    }
+
+    #[test]
+    fn command_words_test() {
+        assert_eq!(command_words("git status"), vec!["git"]);
+        assert_eq!(
+            command_words("FOO=1 ll | grep x && gs; echo $(gl)"),
+            vec!["ll", "grep", "gs", "echo", "gl"]
+        );
+        assert_eq!(
+            command_words("if true; then ll; fi"),
+            vec!["true", "ll", "fi"]
+        );
+    }
+
+    #[test]
+    fn load_order_test() {
+        let aliases = [
+            alias("gll", "gl --oneline", None),
+            alias("ls", "ls --color", None),
+            alias("gl", "git log", None),
+            alias("x", "echo x", Some("y")),
+            alias("y", "echo y", None),
+        ];
+        let refs: Vec<&Alias> = aliases.iter().collect();
+        assert_eq!(load_order(&refs), vec![1, 2, 0, 4, 3]);
+        assert_eq!(find_cycle(&refs, 0), None);
+    }
+
+    #[test]
+    fn cycle_test() {
+        let aliases = [
+            alias("a", "b", None),
+            alias("b", "echo | c", None),
+            alias("c", "echo", Some("a")),
+            alias("d", "echo", None),
+        ];
+        let refs: Vec<&Alias> = aliases.iter().collect();
+        assert_eq!(
+            find_cycle(&refs, 0),
+            Some(vec![
+                "a".to_string(),
+                "b".to_string(),
+                "c".to_string(),
+                "a".to_string()
+            ])
+        );
+        assert_eq!(find_cycle(&refs, 3), None);
+        // Cyclic aliases still get written, after everything else
+        assert_eq!(load_order(&refs), vec![3, 0, 1, 2]);
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use crate::file_management::{Group, Script};
+
+/// The one directory put on PATH for scripts, next to the runcom
+pub fn bin_dir(runcom_file: &Path) -> PathBuf {
+    runcom_file.parent().unwrap_or(Path::new("")).join("bin")
+}
+
+/// Name a script is run by from PATH, the name of its file
+pub fn bin_entry_name(script: &Script) -> Option<&OsStr> {
+    Path::new(&script.path).file_name()
+}
+
+/// Name a script is run by and the absolute path of the file that runs it, its launcher if it
+/// has one
+fn bin_entry(script: &Script) -> Option<(OsString, PathBuf)> {
+    let path = Path::new(&script.path);
+    let name = bin_entry_name(script)?.to_os_string();
+    let launcher = path.parent()?.join("bin").join(&name);
+    let target = if launcher.is_file() {
+        launcher
+    } else {
+        path.to_path_buf()
+    };
+    Some((name, std::path::absolute(target).ok()?))
+}
+
+#[cfg(unix)]
+fn write_entry(target: &Path, entry: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(target, entry)
+}
+
+#[cfg(unix)]
+fn entry_runs(entry: &Path, target: &Path) -> bool {
+    std::fs::read_link(entry).is_ok_and(|link| link == target)
+}
+
+/// Without symlinks each entry is a launcher running its target
+#[cfg(not(unix))]
+fn entry_launcher(target: &Path) -> String {
+    let target = crate::helpers::shell::Shell::Bash.quote(&target.to_string_lossy());
+    format!("#!/bin/sh\nexec {} \"$@\"\n", target)
+}
+
+#[cfg(not(unix))]
+fn write_entry(target: &Path, entry: &Path) -> std::io::Result<()> {
+    std::fs::write(entry, entry_launcher(target))
+}
+
+#[cfg(not(unix))]
+fn entry_runs(entry: &Path, target: &Path) -> bool {
+    std::fs::read_to_string(entry).is_ok_and(|launcher| launcher == entry_launcher(target))
+}
+
+/// Make `bin_dir` hold an entry for every enabled script in `groups` and nothing else
+///
+/// Entries that are already right are left alone, so a shell running a script never sees it
+/// vanish while the directory is rewritten.
+pub fn write_bin_dir(bin_dir: &Path, groups: &[Group]) -> std::io::Result<()> {
+    let wanted: HashMap<OsString, PathBuf> = groups
+        .iter()
+        .flat_map(|group| &group.scripts)
+        .filter(|script| script.enabled)
+        .filter_map(bin_entry)
+        .collect();
+
+    if !bin_dir.is_dir() {
+        if wanted.is_empty() {
+            return Ok(());
+        }
+        std::fs::create_dir(bin_dir)?;
+    }
+    for entry in std::fs::read_dir(bin_dir)? {
+        let entry = entry?;
+        let up_to_date = wanted
+            .get(&entry.file_name())
+            .is_some_and(|target| entry_runs(&entry.path(), target));
+        if up_to_date {
+            continue;
+        }
+        if entry.file_type()?.is_dir() {
+            std::fs::remove_dir_all(entry.path())?;
+        } else {
+            std::fs::remove_file(entry.path())?;
+        }
+    }
+
+    for (name, target) in &wanted {
+        let entry = bin_dir.join(name);
+        if std::fs::symlink_metadata(&entry).is_err() {
+            write_entry(target, &entry)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_bin_dir_test() {
+        let test_dir = Path::new("write_bin_dir_test");
+        let bin = test_dir.join("bin");
+        std::fs::create_dir_all(test_dir.join("scripts/venv_tool/bin"))
+            .expect("Error creating test dirs");
+        let script = |name: &str, file: &str, enabled: bool| Script {
+            enabled,
+            ..Script::for_test(
+                name,
+                test_dir
+                    .join("scripts")
+                    .join(name)
+                    .join(file)
+                    .to_str()
+                    .unwrap(),
+            )
+        };
+        std::fs::write(test_dir.join("scripts/venv_tool/bin/venv_tool.py"), "").unwrap();
+        let group = |scripts: Vec<Script>| Group {
+            id: 1,
+            name: "uncategorized".to_string(),
+            aliases: Vec::new(),
+            scripts,
+        };
+
+        write_bin_dir(
+            &bin,
+            &[group(vec![
+                script("tool", "tool.sh", true),
+                script("venv_tool", "venv_tool.py", true),
+                script("off", "off.sh", false),
+            ])],
+        )
+        .unwrap();
+        let absolute = |path: &str| std::path::absolute(test_dir.join(path)).unwrap();
+        assert!(entry_runs(
+            &bin.join("tool.sh"),
+            &absolute("scripts/tool/tool.sh")
+        ));
+        // Scripts with a launcher are run through it
+        assert!(entry_runs(
+            &bin.join("venv_tool.py"),
+            &absolute("scripts/venv_tool/bin/venv_tool.py")
+        ));
+        assert!(std::fs::symlink_metadata(bin.join("off.sh")).is_err());
+
+        // Entries of scripts that were removed or disabled go
+        write_bin_dir(&bin, &[group(vec![script("tool", "tool.sh", false)])]).unwrap();
+        assert_eq!(std::fs::read_dir(&bin).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
@@ -5,8 +5,8 @@ use super::super::Alias;
 
 pub fn add_alias(conn: &Connection, alias: &Alias) -> Result<(), &'static str> {
     let _ = match conn.execute(
-        "INSERT INTO aliases (name, command, description, enabled, group_id) VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![alias.name, alias.command, alias.description, alias.enabled, alias.group_id],
+        "INSERT INTO aliases (name, command, description, enabled, group_id, after) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+        params![alias.name, alias.command, alias.description, alias.enabled, alias.group_id, alias.after],
     ) {
         Ok(val) => val,
         Err(err) => {
@@ -18,9 +18,22 @@ pub fn add_alias(conn: &Connection, alias: &Alias) -> Result<(), &'static str> {
 }
 
 pub fn get_all_aliases(conn: &Connection) -> Vec<Alias> {
-    let mut alias_query = conn.prepare("SELECT * FROM aliases;").unwrap();
+    query_aliases(conn, "SELECT * FROM aliases ORDER BY id;", [])
+}
+
+/// Get the aliases of one group, ordered by id
+pub fn get_group_aliases(conn: &Connection, group_id: i32) -> Vec<Alias> {
+    query_aliases(
+        conn,
+        "SELECT * FROM aliases WHERE group_id = (?1) ORDER BY id;",
+        [group_id],
+    )
+}
+
+fn query_aliases<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P) -> Vec<Alias> {
+    let mut alias_query = conn.prepare(sql).unwrap();
 
-    let mut rows = alias_query.query([]).unwrap();
+    let mut rows = alias_query.query(params).unwrap();
     let mut aliases = Vec::new();
 
     while let Some(row) = rows.next().unwrap() {
@@ -29,6 +42,7 @@ pub fn get_all_aliases(conn: &Connection) -> Vec<Alias> {
         let description: String = row.get("description").unwrap_or("".to_string());
         let enabled: bool = row.get("enabled").unwrap();
         let group_id: i32 = row.get("group_id").unwrap();
+        let after: Option<String> = row.get("after").unwrap();
 
         aliases.push(Alias {
             name,
@@ -36,6 +50,7 @@ pub fn get_all_aliases(conn: &Connection) -> Vec<Alias> {
             description,
             enabled,
             group_id,
+            after,
         });
     }
     aliases
@@ -53,6 +68,7 @@ pub fn get_alias_by_name(conn: &Connection, name: &str) -> Result<Alias, &'stati
             description: row.get("description").unwrap_or("".to_string()),
             enabled: row.get("enabled").unwrap(),
             group_id: row.get("group_id").unwrap(),
+            after: row.get("after").unwrap(),
         })
     } else {
         Err("Error could not find alias")
@@ -71,22 +87,33 @@ pub fn update_alias(
     old_alias_name: &str,
     updated_alias: Alias,
 ) -> Result<(), &'static str> {
-    match conn.execute(
+    let updated = conn.execute(
         "UPDATE aliases SET 
             name = (?1),
             command = (?2),
             description = (?3),
             enabled = (?4),
-            group_id = (?5)
-        WHERE name = (?6);",
-        [
+            group_id = (?5),
+            after = (?6)
+        WHERE name = (?7);",
+        params![
             updated_alias.name,
             updated_alias.command,
             updated_alias.description,
-            (updated_alias.enabled as i32).to_string(),
-            updated_alias.group_id.to_string(),
-            old_alias_name.to_string(),
+            updated_alias.enabled,
+            updated_alias.group_id,
+            updated_alias.after,
+            old_alias_name,
         ],
+    );
+    if updated.is_err() {
+        return Err("Error updating alias");
+    }
+
+    // Keep aliases ordered after this one pointing at it
+    match conn.execute(
+        "UPDATE aliases SET after = (?1) WHERE after = (?2);",
+        params![updated_alias.name, old_alias_name],
     ) {
         Ok(_) => Ok(()),
         Err(_) => Err("Error updating alias"),
@@ -116,6 +143,7 @@ mod tests {
             description: "".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         };
         let alias2 = Alias {
             name: "alias2".to_string(),
@@ -123,6 +151,7 @@ mod tests {
             description: "description".to_string(),
             enabled: false,
             group_id: 1,
+            after: None,
         };
 
         let group_names = get_group_nameids(&conn).unwrap();
@@ -169,6 +198,7 @@ mod tests {
             description: "description".to_string(),
             enabled: true,
             group_id: 1,
+            after: None,
         };
         let _ = update_alias(&conn, "alias2", updated_alias.clone());
 
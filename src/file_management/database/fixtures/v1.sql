@@ -0,0 +1,36 @@
+-- nym.db as created by nym 0.1.x, before schema versioning (user_version 0)
+CREATE TABLE groups (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE
+);
+CREATE TABLE aliases (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    command TEXT NOT NULL,
+    description TEXT,
+    enabled INTEGER NOT NULL CHECK (enabled IN (0, 1)),
+    group_id INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups (id) 
+);
+CREATE TABLE scripts (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL UNIQUE,
+    path TEXT NOT NULL,
+    description TEXT,
+    enabled INTEGER NOT NULL CHECK (enabled IN (0, 1)),
+    group_id INTEGER NOT NULL,
+    FOREIGN KEY (group_id) REFERENCES groups (id)
+);
+
+INSERT INTO groups (name) VALUES ('uncategorized');
+INSERT INTO groups (name) VALUES ('git');
+
+INSERT INTO aliases (name, command, description, enabled, group_id)
+    VALUES ('gs', 'git status', 'Show working tree status', 1, 2);
+INSERT INTO aliases (name, command, description, enabled, group_id)
+    VALUES ('ll', '''ls -la''', NULL, 0, 1);
+INSERT INTO aliases (name, command, description, enabled, group_id)
+    VALUES ('say', 'echo \"x\" \\ \`date\` \$HOME \n', NULL, 1, 1);
+
+INSERT INTO scripts (name, path, description, enabled, group_id)
+    VALUES ('backup', '/home/user/.nym/scripts/backup/backup.sh', 'Nightly backup', 1, 1);
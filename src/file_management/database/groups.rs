@@ -1,6 +1,5 @@
-use crate::file_management::Script;
-
 use super::super::{Alias, Group};
+use super::{aliases::get_group_aliases, scripts::get_group_scripts};
 use rusqlite::{params, Connection};
 
 pub fn create_group(conn: &Connection, name: &str) {
@@ -13,90 +12,18 @@ pub fn create_group(conn: &Connection, name: &str) {
     };
 }
 
+/// Get every group with its aliases and scripts, ordered by id
 pub fn get_groups(conn: &Connection) -> Vec<Group> {
-    let mut alias_query = conn
-        .prepare(
-            " SELECT
-            g.id as group_id,
-            g.name as group_name,
-            a.id as alias_id,
-            a.name as alias_name,
-            a.command as alias_command,
-            a.description as alias_description,
-            a.enabled as alias_enabled,
-            s.name as script_name,
-            s.path as script_path,
-            s.description as script_description,
-            s.enabled as script_enabled
-        FROM groups g
-        LEFT JOIN aliases a
-        ON g.id = a.group_id
-        LEFT JOIN scripts s 
-        ON g.id = s.group_id;",
-        )
-        .unwrap();
-
-    let mut group_map = std::collections::HashMap::new();
-    let mut rows = alias_query.query([]).unwrap();
-
-    while let Some(row) = rows.next().unwrap() {
-        let group_id: i32 = row.get("group_id").unwrap();
-        let group_name: String = row.get("group_name").unwrap();
-        let alias_name: String = row.get("alias_name").unwrap_or("".to_string());
-        let alias_command: String = row.get("alias_command").unwrap_or("".to_string());
-        let alias_description: String = row.get("description").unwrap_or("".to_string());
-        let alias_enabled: bool = row.get("alias_enabled").unwrap_or(false);
-
-        let script_name: String = row.get("script_name").unwrap_or("".to_string());
-        let script_path: String = row.get("script_path").unwrap_or("".to_string());
-        let script_description: String = row.get("script_description").unwrap_or("".to_string());
-        let script_enabled: bool = row.get("script_enabled").unwrap_or(false);
-
-        let group = group_map.entry(group_id).or_insert_with(|| Group {
-            id: group_id,
-            name: group_name,
-            aliases: Vec::new(),
-            scripts: Vec::new(),
-        });
-
-        if !alias_name.is_empty() && !group.aliases.iter().any(|a| a.name == alias_name) {
-            group.aliases.push(Alias {
-                name: alias_name,
-                command: alias_command,
-                description: alias_description,
-                enabled: alias_enabled,
-                group_id,
-            });
-        }
+    let mut groups = match get_group_nameids(conn) {
+        Ok(groups) => groups,
+        Err(_) => return Vec::new(),
+    };
 
-        if !script_name.is_empty() && !group.scripts.iter().any(|s| s.name == script_name) {
-            group.scripts.push(Script {
-                name: script_name,
-                path: script_path,
-                description: script_description,
-                enabled: script_enabled,
-                group_id,
-            });
-        }
+    for group in groups.iter_mut() {
+        group.aliases = get_group_aliases(conn, group.id);
+        group.scripts = get_group_scripts(conn, group.id);
     }
-    let mut group_query = conn.prepare("SELECT * FROM groups;").unwrap();
-    let mut rows = group_query.query([]).unwrap();
-    while let Some(row) = rows.next().unwrap() {
-        let group_id: i32 = row.get("id").unwrap();
-        let group_name: String = row.get("name").unwrap();
 
-        let _ = group_map.entry(group_id).or_insert_with(|| Group {
-            id: group_id,
-            name: group_name,
-            aliases: Vec::new(),
-            scripts: Vec::new(),
-        });
-    }
-
-    let mut groups = Vec::new();
-    for group in group_map.values() {
-        groups.push(group.clone());
-    }
     groups
 }
 
@@ -109,7 +36,7 @@ pub fn get_group_by_name(conn: &Connection, name: &str) -> Result<Group, &'stati
 
     if let Some(row) = rows.next().unwrap() {
         let mut alias_query = conn
-            .prepare("SELECT * from aliases WHERE group_id = (?1)")
+            .prepare("SELECT * from aliases WHERE group_id = (?1) ORDER BY id")
             .unwrap();
         let group_id: i32 = row.get("id").unwrap();
         let mut aliases = alias_query.query([group_id]).unwrap();
@@ -121,6 +48,7 @@ pub fn get_group_by_name(conn: &Connection, name: &str) -> Result<Group, &'stati
                 description: alias.get("description").unwrap_or("".to_string()),
                 enabled: alias.get("enabled").unwrap(),
                 group_id,
+                after: alias.get("after").unwrap(),
             })
         }
         Ok(Group {
@@ -136,7 +64,7 @@ pub fn get_group_by_name(conn: &Connection, name: &str) -> Result<Group, &'stati
 
 pub fn remove_group(conn: &Connection, name: &str) -> Result<(), String> {
     // Remove group
-    // If group exists move aliases to uncategorized
+    // If group exists move aliases and scripts to uncategorized
     // Do not allow deleting uncategorized (id 1)
     if name == "uncategorized" {
         return Err("Cannot delete uncategorized group".to_string());
@@ -156,6 +84,16 @@ pub fn remove_group(conn: &Connection, name: &str) -> Result<(), String> {
         Err(_) => return Err("Error moving aliases to uncategorized".to_string()),
     };
 
+    if conn
+        .execute(
+            "UPDATE scripts SET group_id = 1 WHERE group_id == (?1)",
+            params![group.id],
+        )
+        .is_err()
+    {
+        return Err("Error moving scripts to uncategorized".to_string());
+    }
+
     match conn.execute("DELETE FROM groups WHERE id == (?1)", [group.id]) {
         Ok(_) => Ok(()),
         Err(err) => Err(format!("Error deleting string: {}", err)),
@@ -175,7 +113,7 @@ pub fn edit_group(conn: &Connection, group_name: &str, group: Group) -> Result<(
 }
 
 pub fn get_group_nameids(conn: &Connection) -> Result<Vec<Group>, &'static str> {
-    let mut group_query = conn.prepare("SELECT * FROM groups;").unwrap();
+    let mut group_query = conn.prepare("SELECT * FROM groups ORDER BY id;").unwrap();
     let mut rows = group_query.query([]).unwrap();
 
     let mut groups = Vec::new();
@@ -200,7 +138,9 @@ mod tests {
     use super::*;
 
     use super::super::aliases::add_alias;
+    use super::super::scripts::add_script;
     use super::super::setupdb;
+    use crate::file_management::Script;
 
     #[test]
     fn groups_database_test() {
@@ -226,7 +166,7 @@ mod tests {
             aliases: Vec::new(),
             scripts: Vec::new(),
         });
-        curr_groups.sort_by(|a, b| a.id.cmp(&b.id));
+        curr_groups.sort_by_key(|g| g.id);
         assert_eq!(curr_groups, add_groups);
 
         let uncategorized = get_group_by_name(&conn, "uncategorized").unwrap();
@@ -240,6 +180,7 @@ mod tests {
                 description: "".to_string(),
                 enabled: true,
                 group_id: 2,
+                after: None,
             },
         );
 
@@ -253,6 +194,7 @@ mod tests {
                 description: "".to_string(),
                 enabled: true,
                 group_id: 2,
+                after: None,
             }],
             scripts: Vec::new(),
         };
@@ -270,6 +212,7 @@ mod tests {
                     description: "".to_string(),
                     enabled: true,
                     group_id: 1,
+                    after: None,
                 }],
                 scripts: Vec::new(),
             }
@@ -277,4 +220,65 @@ mod tests {
 
         std::fs::remove_file("./test.db").expect("Error removing test database");
     }
+
+    #[test]
+    fn get_groups_test() {
+        let db_file = "get_groups_test.db";
+        let conn = setupdb(db_file).unwrap();
+        create_group(&conn, "group1");
+        create_group(&conn, "group2");
+
+        for i in 0..6 {
+            let _ = add_alias(
+                &conn,
+                &Alias {
+                    name: format!("alias{}", i),
+                    command: format!("echo {}", i),
+                    description: format!("description {}", i),
+                    enabled: true,
+                    group_id: i % 2 + 1,
+                    after: None,
+                },
+            );
+            let _ = add_script(
+                &conn,
+                &Script {
+                    group_id: i % 3 + 1,
+                    ..Script::for_test(&format!("script{}", i), &format!("path{}", i))
+                },
+            );
+        }
+
+        let groups = get_groups(&conn);
+        assert_eq!(
+            groups.iter().map(|g| g.id).collect::<Vec<i32>>(),
+            vec![1, 2, 3]
+        );
+
+        // Each item appears exactly once, in insertion order
+        assert_eq!(
+            groups[0]
+                .aliases
+                .iter()
+                .map(|a| a.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["alias0", "alias2", "alias4"]
+        );
+        assert_eq!(groups[1].aliases.len(), 3);
+        assert!(groups[2].aliases.is_empty());
+        assert_eq!(groups[0].aliases[1].description, "description 2");
+
+        assert_eq!(
+            groups[2]
+                .scripts
+                .iter()
+                .map(|s| s.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["script2", "script5"]
+        );
+        assert_eq!(groups[0].scripts.len(), 2);
+        assert_eq!(groups[1].scripts.len(), 2);
+
+        std::fs::remove_file(db_file).expect("Error removing test database");
+    }
 }
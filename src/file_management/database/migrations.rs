@@ -0,0 +1,254 @@
+use rusqlite::{functions::FunctionFlags, Connection};
+
+/// Ordered schema migrations. Running entry `n` moves a database from version `n` to `n + 1`,
+/// the current version is stored in `PRAGMA user_version`.
+///
+/// Never edit a migration that has been released, add a new one to the end instead.
+pub const MIGRATIONS: &[&str] = &[
+    // 1: Initial schema
+    // Databases created before versioning was added already contain these tables (with
+    // user_version 0), so this step has to tolerate them existing.
+    "CREATE TABLE IF NOT EXISTS groups (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE
+    );
+    CREATE TABLE IF NOT EXISTS aliases (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        command TEXT NOT NULL,
+        description TEXT,
+        enabled INTEGER NOT NULL CHECK (enabled IN (0, 1)),
+        group_id INTEGER NOT NULL,
+        FOREIGN KEY (group_id) REFERENCES groups (id)
+    );
+    CREATE TABLE IF NOT EXISTS scripts (
+        id INTEGER PRIMARY KEY,
+        name TEXT NOT NULL UNIQUE,
+        path TEXT NOT NULL,
+        description TEXT,
+        enabled INTEGER NOT NULL CHECK (enabled IN (0, 1)),
+        group_id INTEGER NOT NULL,
+        FOREIGN KEY (group_id) REFERENCES groups (id)
+    );
+    INSERT OR IGNORE INTO groups (name) VALUES ('uncategorized');",
+    // 2: Index group membership lookups
+    "CREATE INDEX IF NOT EXISTS idx_aliases_group_id ON aliases (group_id);
+    CREATE INDEX IF NOT EXISTS idx_scripts_group_id ON scripts (group_id);",
+    // 3: Explicit load ordering, the name of an alias that has to be defined before this one
+    "ALTER TABLE aliases ADD COLUMN after TEXT;",
+    // 4: Where a script was imported from
+    "ALTER TABLE scripts ADD COLUMN source_path TEXT;",
+    // 5: Every version of a script's file, the copies are kept in the script's .versions dir
+    "CREATE TABLE script_versions (
+        id INTEGER PRIMARY KEY,
+        script_id INTEGER NOT NULL,
+        version INTEGER NOT NULL,
+        created_at INTEGER NOT NULL,
+        hash TEXT NOT NULL,
+        message TEXT NOT NULL,
+        file_name TEXT NOT NULL,
+        UNIQUE (script_id, version),
+        FOREIGN KEY (script_id) REFERENCES scripts (id)
+    );",
+    // 6: Scripts stored as a symlink to their source instead of a copy
+    "ALTER TABLE scripts ADD COLUMN linked INTEGER NOT NULL DEFAULT 0 CHECK (linked IN (0, 1));",
+    // 7: Scripts that are a whole directory, the file run inside it
+    "ALTER TABLE scripts ADD COLUMN entry_point TEXT;",
+    // 8: Language of the script, see ScriptType
+    "ALTER TABLE scripts ADD COLUMN type TEXT NOT NULL DEFAULT 'executable';",
+    // 9: Comma separated ScriptIssue names found when the script was last checked
+    "ALTER TABLE scripts ADD COLUMN issues TEXT NOT NULL DEFAULT '';",
+    // 10: How to call a script, from the header of its file
+    "ALTER TABLE scripts ADD COLUMN usage TEXT NOT NULL DEFAULT '';",
+    // 11: Hash of the source file when the script was last copied from it, to spot changes
+    "ALTER TABLE scripts ADD COLUMN source_hash TEXT;",
+    // 12: Opt in to recording runs of a script
+    "ALTER TABLE scripts ADD COLUMN log_runs INTEGER NOT NULL DEFAULT 0 CHECK (log_runs IN (0, 1));",
+    // 13: Runs of scripts made through nym, the output is kept in logs/<script>/<run>.log
+    "CREATE TABLE script_runs (
+        id INTEGER PRIMARY KEY,
+        script_id INTEGER NOT NULL,
+        run INTEGER NOT NULL,
+        started_at INTEGER NOT NULL,
+        duration_ms INTEGER,
+        exit_code INTEGER,
+        args TEXT NOT NULL,
+        UNIQUE (script_id, run),
+        FOREIGN KEY (script_id) REFERENCES scripts (id)
+    );",
+    // 14: When a script runs on its own, see commands::scripts::schedule
+    "ALTER TABLE scripts ADD COLUMN schedule TEXT;",
+    // 15: Alias commands are stored raw, see legacy_alias_command
+    "UPDATE aliases SET command = legacy_alias_command(command);",
+];
+
+/// Raw command of an alias stored by nym 0.1, which kept the quotes around a single-quoted
+/// command and the escapes needed inside the double quotes it wrote aliases in
+fn legacy_alias_command(command: &str) -> String {
+    let command = match command
+        .strip_prefix('\'')
+        .and_then(|c| c.strip_suffix('\''))
+    {
+        Some(unquoted) => unquoted,
+        None => command,
+    };
+
+    let mut raw = String::with_capacity(command.len());
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next @ ('"' | '\\' | '`' | '$'))) => {
+                raw.push(next);
+                chars.next();
+            }
+            _ => raw.push(c),
+        }
+    }
+    raw
+}
+
+pub fn schema_version(conn: &Connection) -> rusqlite::Result<usize> {
+    conn.pragma_query_value(None, "user_version", |row| row.get(0))
+}
+
+/// Bring the database up to the latest schema version
+pub fn migrate(conn: &mut Connection) -> Result<(), String> {
+    run_migrations(conn, MIGRATIONS)
+}
+
+fn run_migrations(conn: &mut Connection, migrations: &[&str]) -> Result<(), String> {
+    let version =
+        schema_version(conn).map_err(|err| format!("Could not read schema version: {}", err))?;
+
+    if version == migrations.len() {
+        return Ok(());
+    }
+    if version > migrations.len() {
+        return Err(format!(
+            "Database schema version {} is newer than this version of nym supports ({})",
+            version,
+            migrations.len()
+        ));
+    }
+
+    conn.create_scalar_function(
+        "legacy_alias_command",
+        1,
+        FunctionFlags::SQLITE_UTF8 | FunctionFlags::SQLITE_DETERMINISTIC,
+        |ctx| Ok(legacy_alias_command(&ctx.get::<String>(0)?)),
+    )
+    .map_err(|err| format!("Could not start migration: {}", err))?;
+
+    // All pending steps share one transaction so a failing step leaves the database untouched
+    let tx = conn
+        .transaction()
+        .map_err(|err| format!("Could not start migration: {}", err))?;
+
+    for (step, migration) in migrations.iter().enumerate().skip(version) {
+        if let Err(err) = tx.execute_batch(migration) {
+            return Err(format!(
+                "Could not migrate database to version {}: {}",
+                step + 1,
+                err
+            ));
+        }
+    }
+
+    tx.pragma_update(None, "user_version", migrations.len())
+        .map_err(|err| format!("Could not update schema version: {}", err))?;
+    tx.commit()
+        .map_err(|err| format!("Could not commit migration: {}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_management::database::{
+        aliases::get_all_aliases, groups::get_group_nameids, scripts::get_all_scripts,
+    };
+
+    /// A database as created by nym before schema versioning was introduced
+    const V1_FIXTURE: &str = include_str!("fixtures/v1.sql");
+
+    #[test]
+    fn migrate_v1_database_test() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(V1_FIXTURE).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+
+        // Existing rows survive and uncategorized is not inserted twice
+        let groups = get_group_nameids(&conn).unwrap();
+        assert_eq!(
+            groups
+                .iter()
+                .map(|g| g.name.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["uncategorized", "git"]
+        );
+
+        let aliases = get_all_aliases(&conn);
+        assert_eq!(aliases.len(), 3);
+        assert_eq!(aliases[0].name, "gs");
+        assert_eq!(aliases[0].command, "git status");
+        assert_eq!(aliases[0].group_id, 2);
+        assert!(!aliases[1].enabled);
+
+        // Commands lose the quoting and escapes nym 0.1 stored with them
+        assert_eq!(aliases[1].command, "ls -la");
+        assert_eq!(aliases[2].command, r#"echo "x" \ `date` $HOME \n"#);
+
+        let scripts = get_all_scripts(&conn);
+        assert_eq!(scripts.len(), 1);
+        assert_eq!(scripts[0].name, "backup");
+
+        // Running again is a no-op
+        migrate(&mut conn).unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+        assert_eq!(get_group_nameids(&conn).unwrap().len(), 2);
+        assert_eq!(get_all_aliases(&conn)[1].command, "ls -la");
+    }
+
+    #[test]
+    fn migrate_new_database_test() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        migrate(&mut conn).unwrap();
+
+        assert_eq!(schema_version(&conn).unwrap(), MIGRATIONS.len());
+        let groups = get_group_nameids(&conn).unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].id, 1);
+        assert_eq!(groups[0].name, "uncategorized");
+    }
+
+    #[test]
+    fn failed_migration_rolls_back_test() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        let migrations = [
+            MIGRATIONS[0],
+            "CREATE TABLE half_applied (id INTEGER); SELECT * FROM missing_table;",
+        ];
+
+        assert!(run_migrations(&mut conn, &migrations).is_err());
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+
+        let tables: i32 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(tables, 0);
+    }
+
+    #[test]
+    fn newer_database_rejected_test() {
+        let mut conn = Connection::open_in_memory().unwrap();
+        conn.pragma_update(None, "user_version", MIGRATIONS.len() + 1)
+            .unwrap();
+        assert!(migrate(&mut conn).is_err());
+    }
+}
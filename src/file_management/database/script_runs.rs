@@ -0,0 +1,164 @@
+use rusqlite::{params, Connection};
+
+use crate::file_management::ScriptRun;
+
+/// Record that a run of the script called `script_name` started, returns the run's number
+///
+/// The number is picked by the insert itself so runs started at the same time get different ones.
+pub fn start_script_run(
+    conn: &Connection,
+    script_name: &str,
+    started_at: i64,
+    args: &str,
+) -> Result<i32, &'static str> {
+    match conn.execute(
+        "INSERT INTO script_runs (script_id, run, started_at, args)
+        SELECT id, COALESCE((SELECT MAX(run) FROM script_runs WHERE script_id = scripts.id), 0) + 1, ?2, ?3
+        FROM scripts WHERE name = ?1;",
+        params![script_name, started_at, args],
+    ) {
+        Ok(1) => conn
+            .query_row(
+                "SELECT run FROM script_runs WHERE id = ?1;",
+                [conn.last_insert_rowid()],
+                |row| row.get(0),
+            )
+            .map_err(|_| "Error reading script run from database"),
+        Ok(_) => Err("Script could not be found"),
+        Err(_) => Err("Error adding script run to database"),
+    }
+}
+
+/// Record how a run of a script ended
+pub fn finish_script_run(
+    conn: &Connection,
+    script_name: &str,
+    run: i32,
+    duration_ms: i64,
+    exit_code: i32,
+) -> Result<(), &'static str> {
+    match conn.execute(
+        "UPDATE script_runs SET duration_ms = ?3, exit_code = ?4
+        WHERE run = ?2 AND script_id IN (SELECT id FROM scripts WHERE name = ?1);",
+        params![script_name, run, duration_ms, exit_code],
+    ) {
+        Ok(1) => Ok(()),
+        Ok(_) => Err("Script run could not be found"),
+        Err(_) => Err("Error updating script run"),
+    }
+}
+
+/// All recorded runs of a script, oldest first
+pub fn get_script_runs(conn: &Connection, script_name: &str) -> Vec<ScriptRun> {
+    let mut run_query = conn
+        .prepare(
+            "SELECT script_runs.* FROM script_runs
+            JOIN scripts ON scripts.id = script_runs.script_id
+            WHERE scripts.name = ?1 ORDER BY run;",
+        )
+        .unwrap();
+
+    let mut rows = run_query.query([script_name]).unwrap();
+    let mut runs = Vec::new();
+
+    while let Some(row) = rows.next().unwrap() {
+        runs.push(ScriptRun {
+            run: row.get("run").unwrap(),
+            started_at: row.get("started_at").unwrap(),
+            duration_ms: row.get("duration_ms").unwrap(),
+            exit_code: row.get("exit_code").unwrap(),
+            args: row.get("args").unwrap(),
+        });
+    }
+    runs
+}
+
+/// Forget all but the `keep` latest runs of a script
+pub fn remove_old_script_runs(
+    conn: &Connection,
+    script_name: &str,
+    keep: usize,
+) -> Result<(), &'static str> {
+    match conn.execute(
+        "DELETE FROM script_runs
+        WHERE script_id IN (SELECT id FROM scripts WHERE name = ?1)
+        AND run <= (SELECT MAX(run) FROM script_runs
+            WHERE script_id IN (SELECT id FROM scripts WHERE name = ?1)) - ?2;",
+        params![script_name, keep as i64],
+    ) {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Error deleting old script runs"),
+    }
+}
+
+/// Forget every run of a script, used when the script is removed
+pub fn remove_script_runs(conn: &Connection, script_name: &str) -> Result<(), &'static str> {
+    match conn.execute(
+        "DELETE FROM script_runs
+        WHERE script_id IN (SELECT id FROM scripts WHERE name = ?1);",
+        params![script_name],
+    ) {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Error deleting script runs"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_management::{
+        database::{scripts::add_script, setupdb},
+        Script,
+    };
+
+    use super::*;
+
+    #[test]
+    fn script_runs_sql_test() {
+        let db_file = "script_runs_sql_test.db";
+        let conn = setupdb(db_file).unwrap();
+
+        let script = Script {
+            log_runs: true,
+            ..Script::for_test("logged", "logged.sh")
+        };
+        add_script(&conn, &script).unwrap();
+
+        for started_at in 1..=3 {
+            assert_eq!(
+                start_script_run(&conn, "logged", started_at, "'a b'"),
+                Ok(started_at as i32)
+            );
+        }
+        assert!(start_script_run(&conn, "missing", 0, "").is_err());
+        assert!(finish_script_run(&conn, "logged", 2, 1500, 1).is_ok());
+        assert!(finish_script_run(&conn, "logged", 4, 1500, 1).is_err());
+
+        let runs = get_script_runs(&conn, "logged");
+        assert_eq!(runs.len(), 3);
+        assert_eq!(
+            runs[1],
+            ScriptRun {
+                run: 2,
+                started_at: 2,
+                duration_ms: Some(1500),
+                exit_code: Some(1),
+                args: "'a b'".to_string(),
+            }
+        );
+        assert_eq!(runs[2].exit_code, None);
+
+        // Numbers keep counting up after old runs are forgotten
+        assert!(remove_old_script_runs(&conn, "logged", 2).is_ok());
+        assert_eq!(start_script_run(&conn, "logged", 4, ""), Ok(4));
+        let runs = get_script_runs(&conn, "logged");
+        assert_eq!(
+            runs.iter().map(|run| run.run).collect::<Vec<_>>(),
+            vec![2, 3, 4]
+        );
+
+        assert!(remove_script_runs(&conn, "logged").is_ok());
+        assert!(get_script_runs(&conn, "logged").is_empty());
+
+        std::fs::remove_file(db_file).expect("Error cleaning test files");
+    }
+}
@@ -0,0 +1,106 @@
+use rusqlite::{params, Connection};
+
+use crate::file_management::ScriptVersion;
+
+/// Save `version` for the script called `script_name`
+pub fn add_script_version(
+    conn: &Connection,
+    script_name: &str,
+    version: &ScriptVersion,
+) -> Result<(), &'static str> {
+    match conn.execute(
+        "INSERT INTO script_versions (script_id, version, created_at, hash, message, file_name)
+        SELECT id, ?2, ?3, ?4, ?5, ?6 FROM scripts WHERE name = ?1;",
+        params![
+            script_name,
+            version.version,
+            version.created_at,
+            version.hash,
+            version.message,
+            version.file_name,
+        ],
+    ) {
+        Ok(1) => Ok(()),
+        Ok(_) => Err("Script could not be found"),
+        Err(_) => Err("Error adding script version to database"),
+    }
+}
+
+/// All saved versions of a script, oldest first
+pub fn get_script_versions(conn: &Connection, script_name: &str) -> Vec<ScriptVersion> {
+    let mut version_query = conn
+        .prepare(
+            "SELECT script_versions.* FROM script_versions
+            JOIN scripts ON scripts.id = script_versions.script_id
+            WHERE scripts.name = ?1 ORDER BY version;",
+        )
+        .unwrap();
+
+    let mut rows = version_query.query([script_name]).unwrap();
+    let mut versions = Vec::new();
+
+    while let Some(row) = rows.next().unwrap() {
+        versions.push(ScriptVersion {
+            version: row.get("version").unwrap(),
+            created_at: row.get("created_at").unwrap(),
+            hash: row.get("hash").unwrap(),
+            message: row.get("message").unwrap(),
+            file_name: row.get("file_name").unwrap(),
+        });
+    }
+    versions
+}
+
+/// Forget every version of a script, used when the script is removed
+pub fn remove_script_versions(conn: &Connection, script_name: &str) -> Result<(), &'static str> {
+    match conn.execute(
+        "DELETE FROM script_versions
+        WHERE script_id IN (SELECT id FROM scripts WHERE name = ?1);",
+        params![script_name],
+    ) {
+        Ok(_) => Ok(()),
+        Err(_) => Err("Error deleting script versions"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::file_management::{
+        database::{scripts::add_script, setupdb},
+        Script,
+    };
+
+    use super::*;
+
+    #[test]
+    fn script_versions_sql_test() {
+        let db_file = "script_versions_sql_test.db";
+        let conn = setupdb(db_file).unwrap();
+
+        let script = Script::for_test("versioned", "versioned.sh");
+        add_script(&conn, &script).unwrap();
+
+        let version = |version: i32| ScriptVersion {
+            version,
+            created_at: 1_700_000_000 + version as i64,
+            hash: format!("hash{}", version),
+            message: format!("version {}", version),
+            file_name: "versioned.sh".to_string(),
+        };
+        assert!(add_script_version(&conn, "versioned", &version(2)).is_ok());
+        assert!(add_script_version(&conn, "versioned", &version(1)).is_ok());
+        assert!(add_script_version(&conn, "versioned", &version(1)).is_err());
+        assert!(add_script_version(&conn, "missing", &version(1)).is_err());
+
+        assert_eq!(
+            get_script_versions(&conn, "versioned"),
+            vec![version(1), version(2)]
+        );
+        assert!(get_script_versions(&conn, "missing").is_empty());
+
+        assert!(remove_script_versions(&conn, "versioned").is_ok());
+        assert!(get_script_versions(&conn, "versioned").is_empty());
+
+        std::fs::remove_file(db_file).expect("Error cleaning test files");
+    }
+}
@@ -1,17 +1,41 @@
 use rusqlite::{params, Connection};
 
-use crate::file_management::Script;
+use crate::file_management::{Script, ScriptIssue, ScriptType};
+
+fn issues_to_string(issues: &[ScriptIssue]) -> String {
+    issues
+        .iter()
+        .map(|issue| issue.as_str())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn issues_from_string(issues: &str) -> Vec<ScriptIssue> {
+    issues
+        .split(',')
+        .filter_map(ScriptIssue::from_name)
+        .collect()
+}
 
 pub fn add_script(conn: &Connection, script: &Script) -> Result<(), &'static str> {
     println!("!Adding script: {:?}", script);
     match conn.execute(
-        "INSERT INTO scripts (name, path, description, enabled, group_id) VALUES (?1, ?2, ?3, ?4, ?5);",
-        [
-            script.name.to_string(),
-            script.path.to_string(),
-            script.description.to_string(),
-            (script.enabled as i32).to_string(),
-            script.group_id.to_string() 
+        "INSERT INTO scripts (name, path, description, enabled, group_id, source_path, source_hash, linked, entry_point, type, issues, usage, log_runs, schedule) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14);",
+        params![
+            script.name,
+            script.path,
+            script.description,
+            script.enabled,
+            script.group_id,
+            script.source_path,
+            script.source_hash,
+            script.linked,
+            script.entry_point,
+            script.type_.as_str(),
+            issues_to_string(&script.issues),
+            script.usage,
+            script.log_runs,
+            script.schedule,
         ],
     ) {
         Ok(_) => Ok(()),
@@ -20,9 +44,22 @@ pub fn add_script(conn: &Connection, script: &Script) -> Result<(), &'static str
 }
 
 pub fn get_all_scripts(conn: &Connection) -> Vec<Script> {
-    let mut script_query = conn.prepare("SELECT * FROM scripts;").unwrap();
+    query_scripts(conn, "SELECT * FROM scripts ORDER BY id;", [])
+}
+
+/// Get the scripts of one group, ordered by id
+pub fn get_group_scripts(conn: &Connection, group_id: i32) -> Vec<Script> {
+    query_scripts(
+        conn,
+        "SELECT * FROM scripts WHERE group_id = (?1) ORDER BY id;",
+        [group_id],
+    )
+}
+
+fn query_scripts<P: rusqlite::Params>(conn: &Connection, sql: &str, params: P) -> Vec<Script> {
+    let mut script_query = conn.prepare(sql).unwrap();
 
-    let mut rows = script_query.query([]).unwrap();
+    let mut rows = script_query.query(params).unwrap();
     let mut scripts = Vec::new();
 
     while let Some(row) = rows.next().unwrap() {
@@ -31,6 +68,15 @@ pub fn get_all_scripts(conn: &Connection) -> Vec<Script> {
         let description: String = row.get("description").unwrap_or("".to_string());
         let enabled: bool = row.get("enabled").unwrap();
         let group_id: i32 = row.get("group_id").unwrap();
+        let source_path: Option<String> = row.get("source_path").unwrap();
+        let source_hash: Option<String> = row.get("source_hash").unwrap();
+        let linked: bool = row.get("linked").unwrap();
+        let entry_point: Option<String> = row.get("entry_point").unwrap();
+        let type_: String = row.get("type").unwrap();
+        let issues: String = row.get("issues").unwrap();
+        let usage: String = row.get("usage").unwrap();
+        let log_runs: bool = row.get("log_runs").unwrap();
+        let schedule: Option<String> = row.get("schedule").unwrap();
 
         scripts.push(Script {
             name,
@@ -38,6 +84,15 @@ pub fn get_all_scripts(conn: &Connection) -> Vec<Script> {
             description,
             enabled,
             group_id,
+            source_path,
+            source_hash,
+            linked,
+            entry_point,
+            type_: ScriptType::from_name(&type_),
+            issues: issues_from_string(&issues),
+            usage,
+            log_runs,
+            schedule,
         });
     }
     scripts
@@ -55,6 +110,15 @@ pub fn get_script_by_name(conn: &Connection, script_name: &str) -> Result<Script
             description: row.get("description").unwrap_or("".to_string()),
             enabled: row.get("enabled").unwrap(),
             group_id: row.get("group_id").unwrap(),
+            source_path: row.get("source_path").unwrap(),
+            source_hash: row.get("source_hash").unwrap(),
+            linked: row.get("linked").unwrap(),
+            entry_point: row.get("entry_point").unwrap(),
+            type_: ScriptType::from_name(&row.get::<_, String>("type").unwrap()),
+            issues: issues_from_string(&row.get::<_, String>("issues").unwrap()),
+            usage: row.get("usage").unwrap(),
+            log_runs: row.get("log_runs").unwrap(),
+            schedule: row.get("schedule").unwrap(),
         })
     } else {
         Err("Script could not be found")
@@ -72,14 +136,32 @@ pub fn update_script(
         path = (?2),
         description = (?3),
         enabled = (?4),
-        group_id = (?5)
-    WHERE name = (?6)",
+        group_id = (?5),
+        source_path = (?6),
+        source_hash = (?7),
+        linked = (?8),
+        entry_point = (?9),
+        type = (?10),
+        issues = (?11),
+        usage = (?12),
+        log_runs = (?13),
+        schedule = (?14)
+    WHERE name = (?15)",
         params![
             new_script.name,
             new_script.path,
             new_script.description,
             new_script.enabled,
             new_script.group_id,
+            new_script.source_path,
+            new_script.source_hash,
+            new_script.linked,
+            new_script.entry_point,
+            new_script.type_.as_str(),
+            issues_to_string(&new_script.issues),
+            new_script.usage,
+            new_script.log_runs,
+            new_script.schedule,
             old_script_name
         ],
     ) {
@@ -100,22 +182,19 @@ pub fn remove_script(conn: &Connection, script_name: &str) -> Result<(), &'stati
 
 #[cfg(test)]
 mod tests {
-    use crate::file_management::database::db_conn;
+    use crate::file_management::database::setupdb;
 
     use super::*;
 
     #[test]
     fn scripts_sql_test() {
         let db_file = "scripts_sql_test.db";
-        let conn = db_conn(db_file);
+        let conn = setupdb(db_file).unwrap();
         assert!(std::path::Path::new(db_file).exists());
 
         let script = Script {
-            name: "test_script".to_string(),
-            path: "test_path".to_string(),
             description: "test script".to_string(),
-            enabled: true,
-            group_id: 1,
+            ..Script::for_test("test_script", "test_path")
         };
 
         assert!(add_script(&conn, &script).is_ok());
@@ -129,11 +208,10 @@ mod tests {
         assert_eq!(all_scripts[0].name, "test_script");
 
         let new_script = Script {
-            name: "new_test_script".to_string(),
-            path: "test_path".to_string(),
             description: "new test script".to_string(),
-            enabled: true,
-            group_id: 1,
+            issues: vec![ScriptIssue::NotExecutable, ScriptIssue::NoShebang],
+            usage: "new_test_script <file>".to_string(),
+            ..Script::for_test("new_test_script", "test_path")
         };
 
         assert!(update_script(&conn, "test_script", &new_script).is_ok());
@@ -141,6 +219,8 @@ mod tests {
         let all_scripts = get_all_scripts(&conn);
         assert_eq!(all_scripts.len(), 1);
         assert_eq!(all_scripts[0].name, "new_test_script");
+        assert_eq!(all_scripts[0].issues, new_script.issues);
+        assert_eq!(all_scripts[0].usage, new_script.usage);
 
         let get_script_by_name = get_script_by_name(&conn, "test_script");
         assert!(get_script_by_name.is_err());
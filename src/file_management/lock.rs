@@ -0,0 +1,64 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::path::Path;
+
+use crate::info;
+
+/// Advisory lock on the nym config dir, released when dropped
+///
+/// Held for the duration of commands that change the database or runcom so two nym invocations
+/// can't interleave their writes.
+pub struct NymLock {
+    _file: File,
+}
+
+fn open_lock_file(nym_dir: &Path) -> Result<File, String> {
+    OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(nym_dir.join(".lock"))
+        .map_err(|err| format!("Could not open lock file: {}", err))
+}
+
+/// Try to take the lock without waiting, returns `Ok(None)` if another process holds it
+pub fn try_lock_nym_dir(nym_dir: &Path) -> Result<Option<NymLock>, String> {
+    let file = open_lock_file(nym_dir)?;
+    match file.try_lock() {
+        Ok(()) => Ok(Some(NymLock { _file: file })),
+        Err(TryLockError::WouldBlock) => Ok(None),
+        Err(TryLockError::Error(err)) => Err(format!("Could not lock nym directory: {}", err)),
+    }
+}
+
+/// Take the lock, waiting for any other nym process holding it to finish
+pub fn lock_nym_dir(nym_dir: &Path) -> Result<NymLock, String> {
+    if let Some(lock) = try_lock_nym_dir(nym_dir)? {
+        return Ok(lock);
+    }
+
+    info!("Waiting for another nym process to finish");
+    let file = open_lock_file(nym_dir)?;
+    match file.lock() {
+        Ok(()) => Ok(NymLock { _file: file }),
+        Err(err) => Err(format!("Could not lock nym directory: {}", err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn nym_lock_test() {
+        let test_dir = Path::new("nym_lock_test");
+        std::fs::create_dir(test_dir).expect("Error creating test dir");
+
+        let lock = lock_nym_dir(test_dir).unwrap();
+        assert!(try_lock_nym_dir(test_dir).unwrap().is_none());
+
+        drop(lock);
+        assert!(try_lock_nym_dir(test_dir).unwrap().is_some());
+
+        std::fs::remove_dir_all(test_dir).expect("Error cleaning up test files");
+    }
+}
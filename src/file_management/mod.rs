@@ -1,17 +1,88 @@
-use database::setupdb;
-use runcom::write_to_runcom;
+use bin_dir::{bin_dir, write_bin_dir};
+use database::groups::get_groups;
+use runcom::{write_atomic, write_to_runcom};
+use rusqlite::Connection;
 
-use crate::{commands::get_groups_and_aliases, error};
+use crate::helpers::shell::Shell;
 
+pub mod alias_order;
+pub mod bin_dir;
 pub mod database;
+pub mod lock;
 pub mod runcom;
 
-// #[allow(dead_code)]
-// #[derive(Debug)]
-// pub enum ScriptType {
-//     Executable,
-//     Python,
-// }
+/// Language a script is written in, which decides what runs it
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+pub enum ScriptType {
+    /// Run directly, e.g. a binary or a script in a language nym doesn't know
+    Executable,
+    Python,
+    Node,
+    Ruby,
+    Bash,
+}
+
+impl ScriptType {
+    /// Name stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScriptType::Executable => "executable",
+            ScriptType::Python => "python",
+            ScriptType::Node => "node",
+            ScriptType::Ruby => "ruby",
+            ScriptType::Bash => "bash",
+        }
+    }
+
+    /// Type stored as `name`, unknown names are run directly
+    pub fn from_name(name: &str) -> ScriptType {
+        match name {
+            "python" => ScriptType::Python,
+            "node" => ScriptType::Node,
+            "ruby" => ScriptType::Ruby,
+            "bash" => ScriptType::Bash,
+            _ => ScriptType::Executable,
+        }
+    }
+
+    /// Program that runs scripts of this type, if they aren't run directly
+    pub fn interpreter(self) -> Option<&'static str> {
+        match self {
+            ScriptType::Executable => None,
+            ScriptType::Python => Some("python3"),
+            ScriptType::Node => Some("node"),
+            ScriptType::Ruby => Some("ruby"),
+            ScriptType::Bash => Some("bash"),
+        }
+    }
+}
+
+/// Fixable reason a script can't be run from PATH, found when it is added or updated
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord)]
+pub enum ScriptIssue {
+    /// The file has no execute bits
+    NotExecutable,
+    /// A text file without a `#!` line naming its interpreter
+    NoShebang,
+}
+
+impl ScriptIssue {
+    /// Name stored in the database
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ScriptIssue::NotExecutable => "not_executable",
+            ScriptIssue::NoShebang => "no_shebang",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<ScriptIssue> {
+        match name {
+            "not_executable" => Some(ScriptIssue::NotExecutable),
+            "no_shebang" => Some(ScriptIssue::NoShebang),
+            _ => None,
+        }
+    }
+}
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
 pub struct Script {
@@ -20,7 +91,74 @@ pub struct Script {
     pub description: String,
     pub enabled: bool,
     pub group_id: i32,
-    // pub type_: ScriptType,
+    /// File the script was imported from, used as the default when updating it
+    pub source_path: Option<String>,
+    /// SHA-256 of `source_path` when the script was last copied from it
+    pub source_hash: Option<String>,
+    /// The stored file is a symlink to `source_path` rather than a copy
+    pub linked: bool,
+    /// For a project, the file its launcher runs, relative to the project directory
+    pub entry_point: Option<String>,
+    pub type_: ScriptType,
+    /// Issues found the last time the script was checked
+    pub issues: Vec<ScriptIssue>,
+    /// How to call the script, read from the header of its file
+    pub usage: String,
+    /// Runs through nym are recorded, with their output kept under `logs/<name>`
+    pub log_runs: bool,
+    /// When the script runs on its own, in systemd's `OnCalendar` syntax
+    pub schedule: Option<String>,
+}
+
+#[cfg(test)]
+impl Script {
+    /// Enabled script in the default group with nothing else set, for tests to fill in
+    pub fn for_test(name: &str, path: &str) -> Script {
+        Script {
+            name: name.to_string(),
+            path: path.to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id: 1,
+            source_path: None,
+            source_hash: None,
+            linked: false,
+            entry_point: None,
+            type_: ScriptType::Executable,
+            issues: Vec::new(),
+            usage: "".to_string(),
+            log_runs: false,
+            schedule: None,
+        }
+    }
+}
+
+/// A saved copy of a script's file
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub struct ScriptVersion {
+    /// Numbered from 1 for each script
+    pub version: i32,
+    /// Unix timestamp of when the version was saved
+    pub created_at: i64,
+    /// SHA-256 of the file, hex encoded
+    pub hash: String,
+    pub message: String,
+    /// Name the script file had, the copy is stored as `<version>-<file_name>`
+    pub file_name: String,
+}
+
+/// A recorded run of a script, its output is kept in `logs/<script>/<run>.log`
+#[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
+pub struct ScriptRun {
+    /// Numbered from 1 for each script
+    pub run: i32,
+    /// Unix timestamp of when the run started
+    pub started_at: i64,
+    /// `None` while the run hasn't finished, or if nym was killed before it did
+    pub duration_ms: Option<i64>,
+    pub exit_code: Option<i32>,
+    /// The arguments quoted as they would be typed
+    pub args: String,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
@@ -30,6 +168,8 @@ pub struct Alias {
     pub description: String,
     pub enabled: bool,
     pub group_id: i32,
+    /// Alias that has to be defined before this one in the runcom
+    pub after: Option<String>,
 }
 
 #[derive(Debug, Clone, Eq, PartialEq, PartialOrd, Ord)]
@@ -40,20 +180,106 @@ pub struct Group {
     pub scripts: Vec<Script>,
 }
 
-pub fn update_runcom(runcom_file: &str, db_file: &str) {
-    let conn = match setupdb(db_file) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue conncecting to database");
-            return;
-        }
+/// Apply `changes` in a single transaction and regenerate the runcom and bin directory once they
+/// succeed.
+///
+/// The transaction is only committed after the runcom has been written, so if either the
+/// changes or the runcom write fail the database is rolled back and the database and the rc
+/// file never disagree. The bin directory is rebuilt from the database if anything fails after
+/// it was updated.
+pub fn commit_changes<T, F>(
+    conn: &Connection,
+    runcom_file: &str,
+    shell: Shell,
+    changes: F,
+) -> Result<T, String>
+where
+    F: FnOnce(&Connection) -> Result<T, String>,
+{
+    let tx = conn
+        .unchecked_transaction()
+        .map_err(|err| format!("Could not start transaction: {}", err))?;
+
+    // Dropping the transaction without committing rolls it back
+    let value = changes(&tx)?;
+
+    let previous_runcom = std::fs::read(runcom_file).ok();
+    let groups = get_groups(&tx);
+    let bin = bin_dir(std::path::Path::new(runcom_file));
+    let bin_written = write_bin_dir(&bin, &groups)
+        .map_err(|err| format!("Could not update {}: {}", bin.display(), err));
+
+    let written =
+        bin_written.and_then(|_| write_to_runcom(runcom_file, groups, shell).map_err(String::from));
+    if let Err(err) = written {
+        // Roll back first so the bin directory is rebuilt from the old database
+        drop(tx);
+        let _ = write_bin_dir(&bin, &get_groups(conn));
+        return Err(err);
+    }
+
+    if let Err(err) = tx.commit() {
+        // Put the old runcom and bin directory back so they keep matching the database
+        let _ = match previous_runcom {
+            Some(contents) => write_atomic(std::path::Path::new(runcom_file), &contents, false),
+            None => std::fs::remove_file(runcom_file),
+        };
+        let _ = write_bin_dir(&bin, &get_groups(conn));
+        return Err(format!("Could not save changes: {}", err));
+    }
+    Ok(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use database::{
+        aliases::{add_alias, get_all_aliases},
+        setupdb,
     };
 
-    let groups = get_groups_and_aliases(&conn);
-    match write_to_runcom(runcom_file, groups) {
-        Ok(_) => (),
-        Err(_) => {
-            error!("issue writing aliases to rc file");
-        }
+    #[test]
+    fn commit_changes_rolls_back_test() {
+        let db_file = "commit_changes_test.db";
+        let rc_file = "commit_changes_test_rc";
+        let conn = setupdb(db_file).unwrap();
+
+        let alias = Alias {
+            name: "test".to_string(),
+            command: "echo test".to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id: 1,
+            after: None,
+        };
+
+        // Failing changes are not applied
+        let result: Result<(), String> = commit_changes(&conn, rc_file, Shell::Bash, |tx| {
+            add_alias(tx, &alias)?;
+            Err("failed midway".to_string())
+        });
+        assert!(result.is_err());
+        assert!(get_all_aliases(&conn).is_empty());
+        assert!(!std::path::Path::new(rc_file).exists());
+
+        // Runcom that cannot be written rolls back the database
+        let result = commit_changes(&conn, "missing_dir/nymrc", Shell::Bash, |tx| {
+            add_alias(tx, &alias).map_err(String::from)
+        });
+        assert!(result.is_err());
+        assert!(get_all_aliases(&conn).is_empty());
+
+        // Successful changes are committed and written to the runcom
+        assert!(commit_changes(&conn, rc_file, Shell::Bash, |tx| {
+            add_alias(tx, &alias).map_err(String::from)
+        })
+        .is_ok());
+        assert_eq!(get_all_aliases(&conn), vec![alias]);
+        assert!(std::fs::read_to_string(rc_file)
+            .unwrap()
+            .contains("alias test="));
+
+        std::fs::remove_file(db_file).expect("Error cleaning up test files");
+        std::fs::remove_file(rc_file).expect("Error cleaning up test files");
     }
 }
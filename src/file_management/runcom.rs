@@ -1,15 +1,52 @@
-use crate::file_management::{Alias, Group};
-use fancy_regex::Regex;
+use crate::file_management::{alias_order::load_order, bin_dir::bin_dir, Alias, Group};
+use crate::helpers::shell::Shell;
+use std::io::Write;
 
+/// Parse an alias definition written for any supported shell into its name and command
+fn parse_alias_line(definition: &str) -> Option<(String, String)> {
+    let definition = definition.trim_start();
+    let shell = match definition.strip_prefix("function ") {
+        // bash and zsh open the function body with {, fish doesn't
+        Some(function) if function.lines().next()?.ends_with(" {") => Shell::Bash,
+        Some(_) => Shell::Fish,
+        None => {
+            // bash and zsh use `alias name=value`, fish uses `alias name value`
+            let alias = definition.strip_prefix("alias ")?;
+            match alias.find(['=', ' ']) {
+                Some(i) if alias[i..].starts_with('=') => Shell::Bash,
+                _ => Shell::Fish,
+            }
+        }
+    };
+    shell.parse_alias_definition(definition)
+}
+
+/// Alias definitions in a runcom, a definition continues onto the following lines until it
+/// parses, i.e. to the end of a quoted value or function body
 #[allow(dead_code)]
 fn capture_aliases(from_str: &str) -> Vec<String> {
-    //TODO: split by groups
-    let re = Regex::new(r#"(?:alias\s+)?(\w+)=([\'"])((?:\\.|(?!\2).)*)\2"#).unwrap();
     let mut aliases = Vec::new();
+    let mut lines = from_str.lines();
+
+    while let Some(line) = lines.next() {
+        let line = line.trim_start();
+        if !line.starts_with("alias ") && !line.starts_with("function ") {
+            continue;
+        }
 
-    for cap in re.captures_iter(from_str) {
-        if let Some(matched) = cap.unwrap().get(0) {
-            aliases.push(matched.as_str().to_string());
+        let mut definition = line.to_string();
+        loop {
+            if parse_alias_line(&definition).is_some() {
+                aliases.push(definition);
+                break;
+            }
+            match lines.next() {
+                Some(next) => {
+                    definition.push('\n');
+                    definition.push_str(next);
+                }
+                None => break,
+            }
         }
     }
     aliases
@@ -21,28 +58,19 @@ pub fn read_aliases(runcom_file: &str) -> Result<Vec<Alias>, &'static str> {
         Ok(runcom) => runcom,
         Err(_) => return Err("Error reading from runcom file"),
     };
-    let alias_strings = capture_aliases(&runcom);
-    if alias_strings.is_empty() {
-        return Ok(Vec::new());
-    }
 
-    let mut aliases = Vec::new();
-
-    // Remove beginning "alias " substring
-    // Split into name and command;
-    for alias_string in alias_strings {
-        let line = alias_string[6..].to_string();
-        let split: Vec<&str> = line.split('=').collect();
-        aliases.push(Alias {
-            name: split[0].to_string(),
-            command: split[1][1..split[1].len() - 1]
-                .to_string()
-                .replace('\\', ""),
+    let aliases = capture_aliases(&runcom)
+        .iter()
+        .filter_map(|definition| parse_alias_line(definition))
+        .map(|(name, command)| Alias {
+            name,
+            command,
             description: "".to_string(),
             enabled: true,
             group_id: 0,
+            after: None,
         })
-    }
+        .collect();
     Ok(aliases)
 }
 
@@ -66,7 +94,52 @@ fn pad_str(input: &str, pad_char: char, total_length: i32) -> String {
     padded_string
 }
 
-pub fn write_to_runcom(runcom_file: &str, groups: Vec<Group>) -> Result<(), &'static str> {
+/// A run of consecutive runcom lines belonging to one group
+struct Section<'a> {
+    /// Index into the groups being written
+    group: usize,
+    aliases: Vec<&'a Alias>,
+}
+
+/// Split the runcom into group sections with every enabled alias defined after the aliases it
+/// depends on.
+///
+/// Aliases are written in dependency order, so when an alias depends on one in a later group the
+/// group is split into more than one section. Without cross group dependencies every group with
+/// enabled aliases gets exactly one section, in order.
+fn runcom_sections(groups: &[Group]) -> Vec<Section<'_>> {
+    let enabled: Vec<(usize, &Alias)> = groups
+        .iter()
+        .enumerate()
+        .flat_map(|(i, group)| {
+            group
+                .aliases
+                .iter()
+                .filter(|alias| alias.enabled)
+                .map(move |alias| (i, alias))
+        })
+        .collect();
+    let aliases: Vec<&Alias> = enabled.iter().map(|(_, alias)| *alias).collect();
+
+    let mut sections: Vec<Section> = Vec::new();
+    for i in load_order(&aliases) {
+        let (group, alias) = enabled[i];
+        match sections.last_mut() {
+            Some(section) if section.group == group => section.aliases.push(alias),
+            _ => sections.push(Section {
+                group,
+                aliases: vec![alias],
+            }),
+        }
+    }
+    sections
+}
+
+pub fn write_to_runcom(
+    runcom_file: &str,
+    groups: Vec<Group>,
+    shell: Shell,
+) -> Result<(), &'static str> {
     let mut runcom = String::new();
 
     runcom.push_str(&pad_str("", '#', 30));
@@ -76,45 +149,85 @@ pub fn write_to_runcom(runcom_file: &str, groups: Vec<Group>) -> Result<(), &'st
     runcom.push_str(&pad_str("", '#', 30));
     runcom.push('\n');
 
-    for group in groups {
+    // Every enabled script is linked into the bin directory, see write_bin_dir
+    let scripts_enabled = groups
+        .iter()
+        .flat_map(|group| &group.scripts)
+        .any(|script| script.enabled);
+    if scripts_enabled {
+        let bin = bin_dir(std::path::Path::new(runcom_file));
+        runcom.push_str("\n########## Scripts ##########\n");
+        runcom.push_str(&shell.path_prepend(&bin.to_string_lossy()));
+        runcom.push('\n');
+    }
+
+    for section in runcom_sections(&groups) {
+        let group = &groups[section.group];
         runcom.push_str(&format!("\n########## {} ##########\n", group.name));
-        if !group.aliases.is_empty() {
-            runcom.push_str("########## Aliases ##########\n");
-
-            for alias in group.aliases {
-                if alias.enabled {
-                    // Replace double quotes with \" to escape them
-                    let alias_command = alias.command.replace('\"', "\\\"");
-                    runcom.push_str(&format!("alias {}=\"{}\"\n", alias.name, alias_command));
-                }
-            }
-            runcom.push('\n');
-        }
+        runcom.push_str("########## Aliases ##########\n");
 
-        if !group.scripts.is_empty() {
-            runcom.push_str("########## Scripts ##########\n");
-            for script in group.scripts {
-                if script.enabled {
-                    let script_path = std::path::Path::new(runcom_file)
-                        .parent()
-                        .unwrap()
-                        .join("scripts")
-                        .join(&script.name);
-                    runcom.push_str(&format!(
-                        "export PATH={}:$PATH\n",
-                        script_path.to_str().unwrap()
-                    ));
-                }
-            }
+        for alias in section.aliases {
+            runcom.push_str(&shell.alias_definition(&alias.name, &alias.command));
+            runcom.push('\n');
         }
+        runcom.push('\n');
     }
 
-    match std::fs::write(runcom_file, runcom) {
+    match write_atomic(std::path::Path::new(runcom_file), runcom.as_bytes(), true) {
         Ok(_) => Ok(()),
         Err(_) => Err("Error writing to runcom file"),
     }
 }
 
+/// Path the previous version of the runcom is kept at
+pub fn backup_path(runcom_file: &std::path::Path) -> std::path::PathBuf {
+    let mut file_name = runcom_file.file_name().unwrap_or_default().to_os_string();
+    file_name.push(".bak");
+    runcom_file.with_file_name(file_name)
+}
+
+/// Replace the runcom without ever leaving a partially written file behind
+///
+/// The new contents are written and synced to a temporary file next to the runcom, which is then
+/// renamed over it. With `backup` the previous runcom is kept as a backup, restoring a runcom
+/// leaves the existing backup alone.
+pub(super) fn write_atomic(
+    runcom_file: &std::path::Path,
+    contents: &[u8],
+    backup: bool,
+) -> std::io::Result<()> {
+    let mut tmp_name = std::ffi::OsString::from(".");
+    tmp_name.push(runcom_file.file_name().unwrap_or_default());
+    tmp_name.push(".tmp");
+    let tmp_file = runcom_file.with_file_name(tmp_name);
+
+    let result = (|| {
+        let mut file = std::fs::File::create(&tmp_file)?;
+        file.write_all(contents)?;
+        file.sync_all()?;
+
+        if backup && runcom_file.exists() {
+            std::fs::copy(runcom_file, backup_path(runcom_file))?;
+        }
+        std::fs::rename(&tmp_file, runcom_file)
+    })();
+
+    if result.is_err() {
+        let _ = std::fs::remove_file(&tmp_file);
+        return result;
+    }
+
+    // Make sure the rename itself survives a crash
+    let parent = match runcom_file.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => std::path::Path::new("."),
+    };
+    if let Ok(dir) = std::fs::File::open(parent) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::super::{Alias, Group};
@@ -149,6 +262,7 @@ mod tests {
             description: "".to_string(),
             enabled: true,
             group_id: 0,
+            after: None,
         };
         let alias2 = Alias {
             name: "test_alias_2".to_string(),
@@ -156,6 +270,7 @@ mod tests {
             description: "".to_string(),
             enabled: true,
             group_id: 0,
+            after: None,
         };
         let alias3 = Alias {
             name: "test_alias_3".to_string(),
@@ -163,6 +278,7 @@ mod tests {
             description: "".to_string(),
             enabled: true,
             group_id: 0,
+            after: None,
         };
         let alias4 = Alias {
             name: "test_alias_4".to_string(),
@@ -170,6 +286,7 @@ mod tests {
             description: "".to_string(),
             enabled: false,
             group_id: 0,
+            after: None,
         };
 
         let group1 = Group {
@@ -186,11 +303,124 @@ mod tests {
             scripts: Vec::new(),
         };
 
-        assert_eq!(Ok(()), write_to_runcom("test1rc", vec![group1, group2]));
+        assert_eq!(
+            Ok(()),
+            write_to_runcom("test1rc", vec![group1, group2], Shell::Bash)
+        );
 
         let enabled_aliases = read_aliases("test1rc").unwrap();
         assert_eq!(enabled_aliases, vec![alias1, alias2, alias3]);
 
         std::fs::remove_file("test1rc").expect("Error deleting test files");
     }
+
+    #[test]
+    fn runcom_backup_test() {
+        let rc_file = "runcom_backup_test_rc";
+        let group = |aliases: Vec<Alias>| Group {
+            id: 1,
+            name: "uncategorized".to_string(),
+            aliases,
+            scripts: Vec::new(),
+        };
+        let alias = Alias {
+            name: "test_alias".to_string(),
+            command: "echo test".to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id: 1,
+            after: None,
+        };
+
+        write_to_runcom(rc_file, vec![group(Vec::new())], Shell::Bash).unwrap();
+        assert!(!backup_path(std::path::Path::new(rc_file)).exists());
+        let first = std::fs::read_to_string(rc_file).unwrap();
+
+        write_to_runcom(rc_file, vec![group(vec![alias])], Shell::Bash).unwrap();
+        let backup = backup_path(std::path::Path::new(rc_file));
+        assert_eq!(std::fs::read_to_string(&backup).unwrap(), first);
+        assert_eq!(read_aliases(rc_file).unwrap()[0].name, "test_alias");
+        assert!(!std::path::Path::new(".runcom_backup_test_rc.tmp").exists());
+
+        std::fs::remove_file(rc_file).expect("Error deleting test files");
+        std::fs::remove_file(backup).expect("Error deleting test files");
+    }
+
+    #[test]
+    fn runcom_order_test() {
+        let rc_file = "runcom_order_test_rc";
+        let alias = |name: &str, command: &str, group_id: i32| Alias {
+            name: name.to_string(),
+            command: command.to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id,
+            after: None,
+        };
+        let group1 = Group {
+            id: 1,
+            name: "uncategorized".to_string(),
+            aliases: vec![alias("gll", "gl --oneline", 1), alias("ll", "ls -l", 1)],
+            scripts: Vec::new(),
+        };
+        let group2 = Group {
+            id: 2,
+            name: "git".to_string(),
+            aliases: vec![alias("gs", "git status", 2), alias("gl", "git log", 2)],
+            scripts: Vec::new(),
+        };
+
+        write_to_runcom(rc_file, vec![group1, group2], Shell::Bash).unwrap();
+        let names: Vec<String> = read_aliases(rc_file)
+            .unwrap()
+            .into_iter()
+            .map(|a| a.name)
+            .collect();
+        assert_eq!(names, vec!["ll", "gs", "gl", "gll"]);
+
+        // uncategorized is split around the git group so gll comes after gl
+        let runcom = std::fs::read_to_string(rc_file).unwrap();
+        assert_eq!(
+            runcom
+                .matches("########## uncategorized ##########")
+                .count(),
+            2
+        );
+        assert_eq!(runcom.matches("########## git ##########").count(), 1);
+
+        std::fs::remove_file(rc_file).expect("Error deleting test files");
+    }
+
+    #[test]
+    fn runcom_function_test() {
+        let rc_file = "runcom_function_test_rc";
+        let alias = |name: &str, command: &str| Alias {
+            name: name.to_string(),
+            command: command.to_string(),
+            description: "".to_string(),
+            enabled: true,
+            group_id: 0,
+            after: None,
+        };
+        let aliases = vec![
+            alias(
+                "deploy",
+                "cat <<EOF\nbuilding $1\nEOF\n  make \"$@\" | tee log",
+            ),
+            alias("gs", "git status"),
+        ];
+        let group = Group {
+            id: 1,
+            name: "uncategorized".to_string(),
+            aliases: aliases.clone(),
+            scripts: Vec::new(),
+        };
+
+        write_to_runcom(rc_file, vec![group], Shell::Bash).unwrap();
+        let runcom = std::fs::read_to_string(rc_file).unwrap();
+        assert!(runcom.contains("function deploy {\ncat <<EOF\nbuilding $1\nEOF\n"));
+        assert_eq!(read_aliases(rc_file).unwrap(), aliases);
+
+        std::fs::remove_file(rc_file).expect("Error deleting test files");
+    }
 }
@@ -0,0 +1,36 @@
+use console::style;
+use similar::{ChangeTag, TextDiff};
+
+/// Print a line by line diff between `old` and `new`, removed lines in red and added in green
+pub fn print_diff(old: &str, new: &str) {
+    let diff = TextDiff::from_lines(old, new);
+
+    for change in diff.iter_all_changes() {
+        let line = change.value().trim_end_matches('\n');
+        match change.tag() {
+            ChangeTag::Delete => println!("{}", style(format!("- {}", line)).red()),
+            ChangeTag::Insert => println!("{}", style(format!("+ {}", line)).green()),
+            ChangeTag::Equal => println!("  {}", line),
+        }
+    }
+}
+
+/// Print a unified diff between two files' contents, labelled with `old_name` and `new_name`
+pub fn print_unified_diff(old: &str, new: &str, old_name: &str, new_name: &str) {
+    let diff = TextDiff::from_lines(old, new);
+    let unified = diff.unified_diff().header(old_name, new_name).to_string();
+
+    for line in unified.lines() {
+        if line.starts_with("---") || line.starts_with("+++") {
+            println!("{}", style(line).bold());
+        } else if line.starts_with("@@") {
+            println!("{}", style(line).cyan());
+        } else if line.starts_with('-') {
+            println!("{}", style(line).red());
+        } else if line.starts_with('+') {
+            println!("{}", style(line).green());
+        } else {
+            println!("{}", line);
+        }
+    }
+}
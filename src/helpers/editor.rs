@@ -0,0 +1,44 @@
+use std::io::{IsTerminal, Read};
+
+/// Open `initial` in `$VISUAL`/`$EDITOR` (vi if neither is set) and return the saved text
+///
+/// `file_name` is used for the temporary file so editors can pick syntax highlighting.
+pub fn edit_text(initial: &str, file_name: &str) -> Result<String, String> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or("vi".to_string());
+
+    let tmp_file = std::env::temp_dir().join(format!("nym-{}-{}", std::process::id(), file_name));
+    std::fs::write(&tmp_file, initial).map_err(|err| format!("Could not create file: {}", err))?;
+
+    // The editor may have arguments e.g. `code --wait`
+    let status = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(format!("{} \"$1\"", editor))
+        .arg("sh")
+        .arg(&tmp_file)
+        .status();
+
+    let text = match status {
+        Ok(status) if status.success() => std::fs::read_to_string(&tmp_file)
+            .map_err(|err| format!("Could not read edited file: {}", err)),
+        Ok(_) => Err(format!("{} exited with an error", editor)),
+        Err(err) => Err(format!("Could not start {}: {}", editor, err)),
+    };
+    let _ = std::fs::remove_file(&tmp_file);
+    text
+}
+
+/// Read text piped to nym, or open the editor if stdin is a terminal
+pub fn read_stdin_or_edit(file_name: &str) -> Result<String, String> {
+    let mut stdin = std::io::stdin();
+    if stdin.is_terminal() {
+        return edit_text("", file_name);
+    }
+
+    let mut text = String::new();
+    stdin
+        .read_to_string(&mut text)
+        .map_err(|err| format!("Could not read from stdin: {}", err))?;
+    Ok(text)
+}
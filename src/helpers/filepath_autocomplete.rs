@@ -74,7 +74,7 @@ impl FilePathCompleter {
             })
             .collect();
 
-        matches.sort_by(|a, b| b.1.cmp(&a.1));
+        matches.sort_by_key(|m| std::cmp::Reverse(m.1));
         matches
     }
 }
@@ -48,6 +48,6 @@ macro_rules! exit {
     };
 }
 
-pub(crate) use error;
+// pub(crate) use error;
 // pub(crate) use success;
 // pub(crate) use warning;
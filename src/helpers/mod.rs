@@ -1,3 +1,6 @@
+pub mod diff;
+pub mod editor;
 pub mod filepath_autocomplete;
 pub mod messages;
 pub mod questions;
+pub mod shell;
@@ -0,0 +1,289 @@
+/// Shell the runcom is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+impl Shell {
+    /// Shell named by `$SHELL`, bash if it is unset or not one nym knows
+    pub fn detect() -> Shell {
+        Shell::from_path(&std::env::var("SHELL").unwrap_or_default())
+    }
+
+    fn from_path(shell: &str) -> Shell {
+        match shell.rsplit('/').next().unwrap_or_default() {
+            "zsh" => Shell::Zsh,
+            "fish" => Shell::Fish,
+            _ => Shell::Bash,
+        }
+    }
+
+    /// Quote `text` so this shell reads it back as exactly one word with no expansions
+    pub fn quote(self, text: &str) -> String {
+        match self {
+            // Nothing is special inside single quotes, a quote has to end the string, be
+            // escaped and start a new one
+            Shell::Bash | Shell::Zsh => format!("'{}'", text.replace('\'', r"'\''")),
+            // Fish treats \' and \\ as escapes inside single quotes
+            Shell::Fish => format!("'{}'", text.replace('\\', r"\\").replace('\'', r"\'")),
+        }
+    }
+
+    /// Parse a single shell word, removing quotes and escapes
+    ///
+    /// Returns `None` if the text isn't exactly one word, i.e. it has unquoted whitespace or an
+    /// unterminated quote. Variables and substitutions are kept as text.
+    pub fn parse_word(self, word: &str) -> Option<String> {
+        match self {
+            Shell::Bash | Shell::Zsh => parse_posix_word(word),
+            Shell::Fish => parse_fish_word(word),
+        }
+    }
+
+    /// Definition of an alias in this shell's syntax
+    ///
+    /// Multi-line commands are written as a function with the body copied verbatim, so heredocs
+    /// and indentation survive. Unlike an alias, arguments aren't appended to the last line of a
+    /// function, the body has to use them (`"$@"` or `$argv`).
+    pub fn alias_definition(self, name: &str, command: &str) -> String {
+        let multi_line = command.contains('\n');
+        match self {
+            Shell::Bash | Shell::Zsh if multi_line => {
+                format!("function {} {{\n{}\n}}", name, command)
+            }
+            Shell::Fish if multi_line => format!("function {}\n{}\nend", name, command),
+            Shell::Bash | Shell::Zsh => format!("alias {}={}", name, self.quote(command)),
+            Shell::Fish => format!("alias {} {}", name, self.quote(command)),
+        }
+    }
+
+    /// Name of the shell's executable
+    pub fn program(self) -> &'static str {
+        match self {
+            Shell::Bash => "bash",
+            Shell::Zsh => "zsh",
+            Shell::Fish => "fish",
+        }
+    }
+
+    /// Script for `-c` that loads `runcom` and runs `command_line` with aliases expanded, as an
+    /// interactive shell would
+    ///
+    /// Each line is read after the ones before it have run, so the runcom's aliases are defined
+    /// by the time the command line is parsed. zsh and fish expand aliases without being asked.
+    pub fn run_with_runcom(self, runcom: &str, command_line: &str) -> String {
+        let source = format!("source {}\n{}", self.quote(runcom), command_line);
+        match self {
+            Shell::Bash => format!("shopt -s expand_aliases\n{}", source),
+            Shell::Zsh | Shell::Fish => source,
+        }
+    }
+
+    /// Line prepending a directory to `$PATH`
+    pub fn path_prepend(self, dir: &str) -> String {
+        match self {
+            Shell::Bash | Shell::Zsh => format!("export PATH={}:\"$PATH\"", self.quote(dir)),
+            Shell::Fish => format!("set -gx PATH {} $PATH", self.quote(dir)),
+        }
+    }
+
+    /// Parse a definition written by [`Shell::alias_definition`] into its name and command
+    pub fn parse_alias_definition(self, definition: &str) -> Option<(String, String)> {
+        if let Some(function) = definition.trim_start().strip_prefix("function ") {
+            let (header, rest) = function.split_once('\n')?;
+            let (body, footer) = rest.rsplit_once('\n')?;
+            let name = match self {
+                Shell::Bash | Shell::Zsh => header.strip_suffix(" {")?,
+                Shell::Fish => header,
+            };
+            let closing = if self == Shell::Fish { "end" } else { "}" };
+            return (footer.trim_end() == closing).then(|| (name.to_string(), body.to_string()));
+        }
+
+        let definition = definition.trim().strip_prefix("alias ")?.trim_start();
+        let (name, value) = match self {
+            Shell::Bash | Shell::Zsh => definition.split_once('=')?,
+            Shell::Fish => definition.split_once(' ')?,
+        };
+        Some((name.to_string(), self.parse_word(value)?))
+    }
+}
+
+fn parse_posix_word(word: &str) -> Option<String> {
+    let mut parsed = String::new();
+    let mut chars = word.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => loop {
+                match chars.next()? {
+                    '\'' => break,
+                    c => parsed.push(c),
+                }
+            },
+            '"' => loop {
+                match chars.next()? {
+                    '"' => break,
+                    // Inside double quotes a backslash only escapes characters that would
+                    // otherwise be special
+                    '\\' => match chars.next()? {
+                        c @ ('$' | '`' | '"' | '\\') => parsed.push(c),
+                        '\n' => (),
+                        c => {
+                            parsed.push('\\');
+                            parsed.push(c);
+                        }
+                    },
+                    c => parsed.push(c),
+                }
+            },
+            '\\' => match chars.next()? {
+                '\n' => (),
+                c => parsed.push(c),
+            },
+            c if c.is_whitespace() => return None,
+            c => parsed.push(c),
+        }
+    }
+    Some(parsed)
+}
+
+fn parse_fish_word(word: &str) -> Option<String> {
+    let mut parsed = String::new();
+    let mut chars = word.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => loop {
+                match chars.next()? {
+                    '\'' => break,
+                    '\\' => match chars.next()? {
+                        c @ ('\'' | '\\') => parsed.push(c),
+                        c => {
+                            parsed.push('\\');
+                            parsed.push(c);
+                        }
+                    },
+                    c => parsed.push(c),
+                }
+            },
+            '"' => loop {
+                match chars.next()? {
+                    '"' => break,
+                    '\\' => match chars.next()? {
+                        c @ ('$' | '"' | '\\') => parsed.push(c),
+                        '\n' => (),
+                        c => {
+                            parsed.push('\\');
+                            parsed.push(c);
+                        }
+                    },
+                    c => parsed.push(c),
+                }
+            },
+            '\\' => match chars.next()? {
+                '\n' => (),
+                c => parsed.push(c),
+            },
+            c if c.is_whitespace() => return None,
+            c => parsed.push(c),
+        }
+    }
+    Some(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    #[test]
+    fn quote_test() {
+        assert_eq!(Shell::Bash.quote("echo $HOME"), "'echo $HOME'");
+        assert_eq!(Shell::Bash.quote("it's"), r"'it'\''s'");
+        assert_eq!(Shell::Fish.quote(r"it's \n"), r"'it\'s \\n'");
+        assert_eq!(
+            Shell::Zsh.alias_definition("x", "a=b"),
+            "alias x='a=b'".to_string()
+        );
+        assert_eq!(
+            Shell::Bash.run_with_runcom("my rc", "gs -s"),
+            "shopt -s expand_aliases\nsource 'my rc'\ngs -s"
+        );
+    }
+
+    #[test]
+    fn parse_word_test() {
+        let bash = Shell::Bash;
+        assert_eq!(bash.parse_word(r#""a=b""#), Some("a=b".to_string()));
+        assert_eq!(
+            bash.parse_word(r#""echo \"hi\" \$HOME \n""#),
+            Some(r#"echo "hi" $HOME \n"#.to_string())
+        );
+        assert_eq!(bash.parse_word(r"'it'\''s'"), Some("it's".to_string()));
+        assert_eq!(bash.parse_word(r"a\ b"), Some("a b".to_string()));
+        assert_eq!(bash.parse_word("a b"), None);
+        assert_eq!(bash.parse_word("'open"), None);
+        assert_eq!(Shell::Fish.parse_word(r"'it\'s'"), Some("it's".to_string()));
+    }
+
+    #[test]
+    fn function_definition_test() {
+        let body = "cat <<EOF\n  $1\n}\nEOF\necho \"$@\"";
+        let bash = Shell::Bash.alias_definition("greet", body);
+        assert_eq!(
+            bash,
+            "function greet {\ncat <<EOF\n  $1\n}\nEOF\necho \"$@\"\n}"
+        );
+        assert_eq!(
+            Shell::Bash.parse_alias_definition(&bash),
+            Some(("greet".to_string(), body.to_string()))
+        );
+
+        let fish = Shell::Fish.alias_definition("greet", "echo $argv\necho done");
+        assert_eq!(fish, "function greet\necho $argv\necho done\nend");
+        assert_eq!(
+            Shell::Fish.parse_alias_definition(&fish),
+            Some(("greet".to_string(), "echo $argv\necho done".to_string()))
+        );
+    }
+
+    #[test]
+    fn detect_test() {
+        assert_eq!(Shell::from_path("/bin/bash"), Shell::Bash);
+        assert_eq!(Shell::from_path("/usr/bin/zsh"), Shell::Zsh);
+        assert_eq!(Shell::from_path("/opt/homebrew/bin/fish"), Shell::Fish);
+        assert_eq!(Shell::from_path(""), Shell::Bash);
+    }
+
+    fn any_shell() -> impl Strategy<Value = Shell> {
+        prop_oneof![Just(Shell::Bash), Just(Shell::Zsh), Just(Shell::Fish)]
+    }
+
+    proptest! {
+        #[test]
+        fn quote_round_trip(shell in any_shell(), text in any::<String>()) {
+            prop_assert_eq!(shell.parse_word(&shell.quote(&text)), Some(text));
+        }
+
+        #[test]
+        fn shell_syntax_round_trip(
+            shell in any_shell(),
+            text in r#"[ a-z'"\\$`!*;|&(){}\n]*"#,
+        ) {
+            prop_assert_eq!(shell.parse_word(&shell.quote(&text)), Some(text));
+        }
+
+        #[test]
+        fn alias_definition_round_trip(
+            shell in any_shell(),
+            name in "[a-z_][a-z0-9_.-]{0,10}",
+            command in any::<String>(),
+        ) {
+            let line = shell.alias_definition(&name, &command);
+            prop_assert_eq!(shell.parse_alias_definition(&line), Some((name, command)));
+        }
+    }
+}
@@ -33,8 +33,6 @@ fn main() {
                     // TODO: Change disabled to enabled and allow true or false to be passed
                 )
                 .subcommand(
-                    // TODO: Add ability to symlink script
-                    // TODO: Add Ability to copy parent dir of script (eg if python script uses venv to run script)
                     Command::new("scripts")
                         .about("List all scripts")
                         .aliases(["script", "s"]),
@@ -54,19 +52,42 @@ fn main() {
                     Command::new("alias")
                         .about("Create a new alias")
                         .arg(
-                            arg!(<command> "The command to run when the alias is called")
+                            arg!(<command> "name=command, or just the name to read the command from stdin or $EDITOR")
                                 .num_args(1..)
                                 .allow_hyphen_values(true),
                         )
                         .arg(arg!(-d --description [DESCRIPTION] "A description of the aliase"))
-                        .arg(arg!(-g --group [GROUP] "The group to add the alias to")),
+                        .arg(arg!(-g --group [GROUP] "The group to add the alias to"))
+                        .arg(arg!(-a --after [ALIAS] "An alias that has to be defined before this one"))
+                        .arg(arg!(--strict "Refuse names that shadow commands or other nym items")),
                 )
                 .subcommand(
                     Command::new("script")
                         .about("Add a new script")
-                        .arg(arg!(<path> "The path to the script"))
+                        .arg(arg!(<path> "The path to the script, or a directory to add as a project"))
+                        .arg(arg!(-e --entry [FILE] "For a directory, the file to run inside it"))
+                        .arg(arg!(-d --description [DESCRIPTION] "A description of the script, read from its header if not given"))
+                        .arg(arg!(-g --group [GROUP] "The group to add the script to, read from its header if not given"))
+                        .arg(arg!(--strict "Refuse names that shadow commands or other nym items"))
+                        .arg(arg!(-l --link "Symlink the script instead of copying it"))
+                        .arg(arg!(--fix "Make the script executable and add a #! line without asking, linked scripts are left as they are")),
+                ),
+        )
+        .subcommand(
+            Command::new("new")
+                .about("Create a new item from a template")
+                .subcommand(
+                    Command::new("script")
+                        .about("Create a script from a template in ~/.nym/templates and open it in $EDITOR")
+                        .arg(arg!(<name> "The name of the script"))
+                        .arg(
+                            arg!(-l --lang <LANG> "The language of the script")
+                                .value_parser(crate::commands::scripts::template::LANGUAGES)
+                                .default_value("bash"),
+                        )
                         .arg(arg!(-d --description [DESCRIPTION] "A description of the script"))
-                        .arg(arg!(-g --group [GROUP] "The group to add the script to")), // .arg(arg!(-ln --link [LINK] "Use symlink instead of copying script")),
+                        .arg(arg!(-g --group [GROUP] "The group to add the script to"))
+                        .arg(arg!(--strict "Refuse names that shadow commands or other nym items")),
                 ),
         )
         .subcommand(
@@ -81,7 +102,8 @@ fn main() {
             Command::new("rename")
                 .about("Rename an alias or group")
                 .arg(arg!(<old_name> "Name of item to rename"))
-                .arg(arg!(<new_name> "New Name of item")),
+                .arg(arg!(<new_name> "New Name of item"))
+                .arg(arg!(--strict "Refuse names that shadow commands or other nym items")),
         )
         .subcommand(
             Command::new("toggle")
@@ -104,7 +126,94 @@ fn main() {
                 .about("Update alias or script")
                 .arg(arg!(<name> "name of item to update"))
                 .arg(arg!(-n --new <NEW_ITEM> "Either the new command for the alias or path to updated script"))
-            ,
+                .arg(arg!(-d --description <DESCRIPTION> "The new description for the alias"))
+                .arg(arg!(-m --message <MESSAGE> "A note saved with the new version of a script"))
+                .arg(arg!(-y --yes "Replace a script without asking for confirmation"))
+                .arg(arg!(--fix "Make a script executable and add a #! line without asking")),
+        )
+        .subcommand(
+            Command::new("script")
+                .about("Manage the saved versions, sources and run logs of scripts")
+                .subcommand(
+                    Command::new("history")
+                        .about("List the saved versions of a script")
+                        .arg(arg!(<name> "The name of the script")),
+                )
+                .subcommand(
+                    Command::new("diff")
+                        .about("Show changes between versions, or a version and the current script")
+                        .arg(arg!(<name> "The name of the script"))
+                        .arg(arg!([old] "The older version").value_parser(clap::value_parser!(i32)))
+                        .arg(arg!([new] "The newer version, the current script if not given").value_parser(clap::value_parser!(i32))),
+                )
+                .subcommand(
+                    Command::new("rollback")
+                        .about("Restore a saved version of a script")
+                        .arg(arg!(<name> "The name of the script"))
+                        .arg(arg!(<version> "The version to restore").value_parser(clap::value_parser!(i32)))
+                        .arg(arg!(-y --yes "Restore without asking for confirmation")),
+                )
+                .subcommand(
+                    Command::new("status")
+                        .about("List scripts whose source changed or went missing, or whose copy was edited"),
+                )
+                .subcommand(
+                    Command::new("sync")
+                        .about("Pull changes from the sources of scripts")
+                        .arg(arg!([name] "The script to sync, every script whose source changed if not given"))
+                        .arg(arg!(-y --yes "Sync without asking for confirmation")),
+                )
+                .subcommand(
+                    Command::new("logging")
+                        .about("Turn logging runs of a script through `nym exec` on or off")
+                        .arg(arg!(<name> "The name of the script"))
+                        .arg(arg!(<state> "Whether runs are logged").value_parser(["on", "off"])),
+                )
+                .subcommand(
+                    Command::new("runs")
+                        .about("List the recent logged runs of a script")
+                        .arg(arg!(<name> "The name of the script")),
+                )
+                .subcommand(
+                    Command::new("log")
+                        .about("Show the output of a logged run of a script")
+                        .arg(arg!(<name> "The name of the script"))
+                        .arg(arg!([run] "The run to show, the latest if not given").value_parser(clap::value_parser!(i32))),
+                ),
+        )
+        .subcommand(
+            Command::new("doctor")
+                .about("Check scripts for problems, e.g. dangling links")
+                .arg(arg!(--fix "Make scripts executable and add missing #! lines, linked scripts are left as they are")),
+        )
+        .subcommand(
+            Command::new("expand")
+                .about("Show what a command line runs after expanding aliases")
+                .arg(
+                    arg!(<command> "The command line to expand")
+                        .num_args(1..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true),
+                ),
+        )
+        .subcommand(
+            Command::new("exec")
+                .about("Run an alias, function or script outside an interactive shell")
+                .visible_alias("run")
+                .arg(arg!(<name> "The name of the alias or script to run"))
+                .arg(
+                    arg!([args] "Arguments to pass on")
+                        .num_args(0..)
+                        .trailing_var_arg(true)
+                        .allow_hyphen_values(true),
+                ),
+        )
+        .subcommand(
+            Command::new("schedule")
+                .about("Run a script on a schedule through systemd timers, or cron without systemd")
+                .arg(arg!(<script> "The name of the script"))
+                .arg(arg!([spec] "When to run it in systemd's calendar syntax, e.g. daily or \"Mon..Fri 09:00\", the current schedule is shown if not given"))
+                .arg(arg!(-r --remove "Stop running the script on a schedule")),
         )
         .subcommand(
             // TODO: Allow creating a new group while moving "move -n group_name"
@@ -132,6 +241,21 @@ fn main() {
         std::process::exit(1);
     }
 
+    // Install and uninstall manage the config dir themselves so they don't open the database
+    match matches.subcommand() {
+        Some(("install", sub_m)) => {
+            let shell_profile = sub_m.get_one::<String>("shell_profile").unwrap();
+            crate::install::install(shell_profile);
+            return;
+        }
+        Some(("uninstall", sub_m)) => {
+            let shell_profile = sub_m.get_one::<String>("shell_profile").unwrap();
+            crate::install::uninstall(shell_profile);
+            return;
+        }
+        _ => (),
+    }
+
     let nym_db = nym_dir
         .join("nym.db")
         .into_os_string()
@@ -143,23 +267,48 @@ fn main() {
         .into_string()
         .unwrap();
 
+    // Commands that change the database or runcom hold the lock until nym exits
+    let _lock = match matches.subcommand_name() {
+        None
+        | Some(
+            "add" | "new" | "remove" | "rename" | "toggle" | "move" | "update" | "script"
+            | "doctor" | "schedule",
+        ) => match crate::file_management::lock::lock_nym_dir(&nym_dir) {
+            Ok(lock) => Some(lock),
+            Err(err) => {
+                error!(err);
+                std::process::exit(1);
+            }
+        },
+        _ => None,
+    };
+
+    // One connection is shared by everything run during this invocation
+    let ctx = match crate::commands::Context::new(&nymrc, &nym_db) {
+        Ok(ctx) => ctx,
+        Err(_) => {
+            error!("issue connecting to database");
+            std::process::exit(1);
+        }
+    };
+
     match matches.subcommand() {
         Some(("list", sub_m)) => match sub_m.subcommand() {
             // TODO: Allow listing specifc group(s) and just aliases or scripts in the group
             Some(("groups", _)) => {
-                crate::commands::groups::list::list_groups(&nym_db);
+                crate::commands::groups::list::list_groups(&ctx);
             }
             Some(("aliases", sub_m)) => {
                 crate::commands::aliases::list::list_aliases(
-                    &nym_db,
+                    &ctx,
                     *sub_m.get_one("disabled").unwrap_or(&false),
                 );
             }
             Some(("scripts", _)) => {
-                crate::commands::scripts::list::list_scripts(&nym_db);
+                crate::commands::scripts::list::list_scripts(&ctx);
             }
             _ => {
-                crate::commands::groups::list::list_all(&nym_db);
+                crate::commands::groups::list::list_all(&ctx);
             }
         },
         Some(("add", sub_m)) => {
@@ -170,7 +319,7 @@ fn main() {
                         .unwrap()
                         .map(|s| s.to_string())
                         .collect();
-                    crate::commands::groups::add::add_group(&nym_db, &name.join(" "));
+                    crate::commands::groups::add::add_group(&ctx, &name.join(" "));
                 }
                 Some(("alias", sub_m)) => {
                     let command_vector: Vec<String> = sub_m
@@ -179,9 +328,15 @@ fn main() {
                         .map(|s| s.to_string())
                         .collect();
 
-                    println!("command vector: {:?}", command_vector);
-
-                    let command = command_vector.join(" ");
+                    let mut command = command_vector.join(" ");
+                    if !command.contains('=') {
+                        command =
+                            match crate::commands::aliases::add::read_alias_command(&ctx, &command)
+                            {
+                                Some(command) => command,
+                                None => return,
+                            };
+                    }
 
                     let description = sub_m
                         .get_one::<String>("description")
@@ -195,17 +350,20 @@ fn main() {
                     let group_id = if group_name.is_empty() {
                         1
                     } else {
-                        crate::commands::groups::ask_fuzzy_get(&nym_db, &group_name)
+                        crate::commands::groups::ask_fuzzy_get(&ctx.conn, &group_name)
                             .unwrap()
                             .id
                     };
 
+                    let after = sub_m.get_one::<String>("after").map(|s| s.as_str());
+
                     crate::commands::aliases::add::add_alias(
-                        &nymrc,
-                        &nym_db,
+                        &ctx,
                         &command,
                         &description,
                         group_id,
+                        after,
+                        sub_m.get_flag("strict"),
                     );
                 }
                 Some(("script", sub_m)) => {
@@ -219,21 +377,42 @@ fn main() {
                         .unwrap_or(&"".to_string())
                         .to_string();
 
+                    // Without a group the script's header can name one
                     let group_id = if group_name.is_empty() {
-                        1
+                        None
                     } else {
-                        crate::commands::groups::ask_fuzzy_get(&nym_db, &group_name)
-                            .unwrap()
-                            .id
+                        Some(
+                            crate::commands::groups::ask_fuzzy_get(&ctx.conn, &group_name)
+                                .unwrap()
+                                .id,
+                        )
                     };
 
-                    crate::commands::scripts::add::add_script(
-                        &nymrc,
-                        &nym_db,
-                        &path,
-                        &description,
-                        group_id,
-                    );
+                    if std::path::Path::new(&path).is_dir() {
+                        crate::commands::scripts::project::add_project(
+                            &ctx,
+                            &path,
+                            sub_m.get_one::<String>("entry").map(|s| s.as_str()),
+                            &description,
+                            group_id,
+                            sub_m.get_flag("strict"),
+                            sub_m.get_flag("link"),
+                        );
+                    } else {
+                        crate::commands::scripts::add::add_script(
+                            &ctx,
+                            &path,
+                            &description,
+                            group_id,
+                            sub_m.get_flag("strict"),
+                            sub_m.get_flag("link"),
+                            if sub_m.get_flag("fix") {
+                                crate::commands::scripts::check::FixIssues::Always
+                            } else {
+                                crate::commands::scripts::check::FixIssues::Ask
+                            },
+                        );
+                    }
                 }
                 _ => {
                     // Display help message
@@ -246,34 +425,41 @@ fn main() {
                 }
             }
         }
+        Some(("new", sub_m)) => match sub_m.subcommand() {
+            Some(("script", sub_m)) => {
+                crate::commands::scripts::template::new_script(
+                    &ctx,
+                    sub_m.get_one::<String>("name").unwrap(),
+                    sub_m.get_one::<String>("lang").unwrap(),
+                    sub_m
+                        .get_one::<String>("description")
+                        .map_or("", |s| s.as_str()),
+                    sub_m.get_one::<String>("group").map(|s| s.as_str()),
+                    sub_m.get_flag("strict"),
+                );
+            }
+            _ => {
+                commands
+                    .find_subcommand("new")
+                    .unwrap()
+                    .clone()
+                    .print_help()
+                    .unwrap();
+            }
+        },
         Some(("remove", sub_m)) => {
             let name = sub_m.get_one::<String>("name").unwrap();
             let force = sub_m.get_one::<bool>("force").unwrap_or(&false);
 
-            match crate::commands::get_item(&nym_db, name, true) {
+            match crate::commands::get_item(&ctx.conn, name, true) {
                 Some(crate::commands::Item::Alias(alias)) => {
-                    crate::commands::aliases::remove::remove_alias(
-                        &nymrc,
-                        &nym_db,
-                        &alias.name,
-                        *force,
-                    );
+                    crate::commands::aliases::remove::remove_alias(&ctx, &alias.name, *force);
                 }
                 Some(crate::commands::Item::Group(group)) => {
-                    crate::commands::groups::remove::remove_group(
-                        &nymrc,
-                        &nym_db,
-                        &group.name,
-                        *force,
-                    );
+                    crate::commands::groups::remove::remove_group(&ctx, &group.name, *force);
                 }
                 Some(crate::commands::Item::Script(script)) => {
-                    crate::commands::scripts::remove::remove_script(
-                        &nymrc,
-                        &nym_db,
-                        &script.name,
-                        *force,
-                    );
+                    crate::commands::scripts::remove::remove_script(&ctx, &script.name, *force);
                 }
                 None => {
                     error!(format!(
@@ -287,15 +473,15 @@ fn main() {
             // TODO: Add ability to specify type
             let name = sub_m.get_one::<String>("name").unwrap();
 
-            match crate::commands::get_item(&nym_db, name, true) {
+            match crate::commands::get_item(&ctx.conn, name, true) {
                 Some(crate::commands::Item::Alias(alias)) => {
-                    crate::commands::aliases::edit::toggle_alias(&nymrc, &nym_db, &alias.name)
+                    crate::commands::aliases::edit::toggle_alias(&ctx, &alias.name)
                 }
                 Some(crate::commands::Item::Group(group)) => {
-                    crate::commands::groups::toggle::toggle_group(&nymrc, &nym_db, &group.name)
+                    crate::commands::groups::toggle::toggle_group(&ctx, &group.name)
                 }
                 Some(crate::commands::Item::Script(script)) => {
-                    crate::commands::scripts::edit::toggle_script(&nymrc, &nym_db, &script.name)
+                    crate::commands::scripts::edit::toggle_script(&ctx, &script.name)
                 }
                 None => {
                     error!(format!(
@@ -307,12 +493,12 @@ fn main() {
         }
         Some(("man", sub_m)) => {
             let name = sub_m.get_one::<String>("name").unwrap();
-            match crate::commands::get_item(&nym_db, name, false) {
+            match crate::commands::get_item(&ctx.conn, name, false) {
                 Some(crate::commands::Item::Alias(alias)) => {
-                    crate::commands::aliases::list::alias_manual(&nym_db, &alias.name);
+                    crate::commands::aliases::list::alias_manual(&ctx, &alias.name);
                 }
                 Some(crate::commands::Item::Script(script)) => {
-                    crate::commands::scripts::list::script_manual(&nym_db, &script.name);
+                    crate::commands::scripts::list::script_manual(&ctx, &script.name);
                 }
                 _ => {
                     error!(format!(
@@ -322,36 +508,24 @@ fn main() {
                 }
             }
         }
-        Some(("install", sub_m)) => {
-            let shell_profile = sub_m.get_one::<String>("shell_profile").unwrap();
-            crate::install::install(shell_profile);
-        }
-        Some(("uninstall", sub_m)) => {
-            let shell_profile = sub_m.get_one::<String>("shell_profile").unwrap();
-            crate::install::uninstall(shell_profile);
-        }
         Some(("rename", sub_m)) => {
             let old_name = sub_m.get_one::<String>("old_name").unwrap();
             let new_name = sub_m.get_one::<String>("new_name").unwrap();
+            let strict = sub_m.get_flag("strict");
 
-            match crate::commands::get_item(&nym_db, old_name, true) {
+            match crate::commands::get_item(&ctx.conn, old_name, true) {
                 Some(crate::commands::Item::Alias(alias)) => {
-                    crate::commands::aliases::edit::rename(&nymrc, &nym_db, &alias.name, new_name);
+                    crate::commands::aliases::edit::rename(&ctx, &alias.name, new_name, strict);
                 }
                 Some(crate::commands::Item::Group(group)) => {
-                    crate::commands::groups::rename::rename_group(
-                        &nymrc,
-                        &nym_db,
-                        &group.name,
-                        new_name,
-                    );
+                    crate::commands::groups::rename::rename_group(&ctx, &group.name, new_name);
                 }
                 Some(crate::commands::Item::Script(script)) => {
                     crate::commands::scripts::edit::rename_script(
-                        &nymrc,
-                        &nym_db,
+                        &ctx,
                         &script.name,
                         new_name,
+                        strict,
                     );
                 }
                 _ => {
@@ -371,27 +545,21 @@ fn main() {
                 .unwrap_or(&"".to_string())
                 .to_string();
 
-            match crate::commands::get_item(&nym_db, name, false) {
+            match crate::commands::get_item(&ctx.conn, name, false) {
                 Some(crate::commands::Item::Alias(alias)) => {
                     crate::commands::aliases::edit::move_alias_group(
-                        &nymrc,
-                        &nym_db,
+                        &ctx,
                         &alias.name,
                         group,
                         if new_group.is_empty() {
-                            None 
+                            None
                         } else {
-                           Some(&new_group) 
-                        }
+                            Some(&new_group)
+                        },
                     );
                 }
                 Some(crate::commands::Item::Script(script)) => {
-                    crate::commands::scripts::edit::move_script(
-                        &nymrc,
-                        &nym_db,
-                        &script.name,
-                        group,
-                    );
+                    crate::commands::scripts::edit::move_script(&ctx, &script.name, group);
                 }
                 _ => {
                     error!(format!(
@@ -403,27 +571,30 @@ fn main() {
         }
         Some(("update", sub_m)) => {
             let name = sub_m.get_one::<String>("name").unwrap();
-            let updated_item = sub_m
-                .get_one::<String>("new")
-                .unwrap_or(&"".to_string())
-                .to_string();
+            let updated_item = sub_m.get_one::<String>("new").map(|s| s.as_str());
 
-            match crate::commands::get_item(&nym_db, name, false) {
+            match crate::commands::get_item(&ctx.conn, name, false) {
                 Some(crate::commands::Item::Alias(alias)) => {
-                    println!("Found alias {}", alias.name);
+                    crate::commands::aliases::update::update_alias(
+                        &ctx,
+                        &alias.name,
+                        updated_item,
+                        sub_m.get_one::<String>("description").map(|s| s.as_str()),
+                    );
                 }
                 Some(crate::commands::Item::Script(script)) => {
                     crate::commands::scripts::update::update_script(
-                        &nym_db,
-                        &nymrc,
+                        &ctx,
                         &script.name,
-                        if updated_item.is_empty() {
-                            None
+                        updated_item,
+                        sub_m.get_one::<String>("message").map(|s| s.as_str()),
+                        sub_m.get_flag("yes"),
+                        if sub_m.get_flag("fix") {
+                            crate::commands::scripts::check::FixIssues::Always
                         } else {
-                            Some(&updated_item)
+                            crate::commands::scripts::check::FixIssues::Ask
                         },
                     );
-                    println!("Found script {}", script.name);
                 }
                 _ => {
                     error!(format!(
@@ -433,8 +604,100 @@ fn main() {
                 }
             }
         }
+        Some(("script", sub_m)) => match sub_m.subcommand() {
+            Some(("history", sub_m)) => {
+                let name = sub_m.get_one::<String>("name").unwrap();
+                crate::commands::scripts::history::script_history(&ctx, name);
+            }
+            Some(("diff", sub_m)) => {
+                let name = sub_m.get_one::<String>("name").unwrap();
+                crate::commands::scripts::history::script_diff(
+                    &ctx,
+                    name,
+                    sub_m.get_one::<i32>("old").copied(),
+                    sub_m.get_one::<i32>("new").copied(),
+                );
+            }
+            Some(("rollback", sub_m)) => {
+                let name = sub_m.get_one::<String>("name").unwrap();
+                let version = *sub_m.get_one::<i32>("version").unwrap();
+                crate::commands::scripts::history::rollback_script(
+                    &ctx,
+                    name,
+                    version,
+                    sub_m.get_flag("yes"),
+                );
+            }
+            Some(("status", _)) => {
+                crate::commands::scripts::status::script_status(&ctx);
+            }
+            Some(("sync", sub_m)) => {
+                crate::commands::scripts::status::sync_scripts(
+                    &ctx,
+                    sub_m.get_one::<String>("name").map(|s| s.as_str()),
+                    sub_m.get_flag("yes"),
+                );
+            }
+            Some(("logging", sub_m)) => {
+                crate::commands::scripts::runs::set_run_logging(
+                    &ctx,
+                    sub_m.get_one::<String>("name").unwrap(),
+                    sub_m.get_one::<String>("state").unwrap() == "on",
+                );
+            }
+            Some(("runs", sub_m)) => {
+                let name = sub_m.get_one::<String>("name").unwrap();
+                crate::commands::scripts::runs::script_runs(&ctx, name);
+            }
+            Some(("log", sub_m)) => {
+                let name = sub_m.get_one::<String>("name").unwrap();
+                crate::commands::scripts::runs::script_log(
+                    &ctx,
+                    name,
+                    sub_m.get_one::<i32>("run").copied(),
+                );
+            }
+            _ => {
+                commands
+                    .find_subcommand("script")
+                    .unwrap()
+                    .clone()
+                    .print_help()
+                    .unwrap();
+            }
+        },
+        Some(("doctor", sub_m)) => {
+            if crate::commands::doctor::doctor(&ctx, sub_m.get_flag("fix")) > 0 {
+                std::process::exit(1);
+            }
+        }
+        Some(("expand", sub_m)) => {
+            let command: Vec<String> = sub_m
+                .get_many::<String>("command")
+                .unwrap()
+                .map(|s| s.to_string())
+                .collect();
+            crate::commands::expand::expand(&ctx, &command.join(" "));
+        }
+        Some(("schedule", sub_m)) => {
+            crate::commands::scripts::schedule::schedule_script(
+                &ctx,
+                sub_m.get_one::<String>("script").unwrap(),
+                sub_m.get_one::<String>("spec").map(|s| s.as_str()),
+                sub_m.get_flag("remove"),
+            );
+        }
+        Some(("exec", sub_m)) => {
+            let name = sub_m.get_one::<String>("name").unwrap();
+            let args: Vec<String> = sub_m
+                .get_many::<String>("args")
+                .unwrap_or_default()
+                .map(|s| s.to_string())
+                .collect();
+            std::process::exit(crate::commands::exec::exec(&ctx, name, &args));
+        }
         _ => {
-            crate::manager::start_manager(&nymrc, &nym_db);
+            crate::manager::start_manager(ctx);
         }
     }
 }
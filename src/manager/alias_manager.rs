@@ -1,16 +1,15 @@
-use crate::{error, helpers, success};
+use crate::{
+    commands::Context,
+    error,
+    file_management::database::aliases::{
+        get_alias_by_name, get_all_aliases, remove_alias, update_alias,
+    },
+    helpers, success,
+};
 use console::style;
 
-pub fn bulk_toggle_aliases(runcom_file: &str, db_file: &str) {
-    let conn = match crate::file_management::database::setupdb(db_file) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to database");
-            return;
-        }
-    };
-
-    let aliases = crate::file_management::database::aliases::get_all_aliases(&conn);
+pub fn bulk_toggle_aliases(ctx: &Context) {
+    let aliases = get_all_aliases(&ctx.conn);
 
     if aliases.is_empty() {
         error!("Could not find any aliases to toggle");
@@ -40,19 +39,23 @@ pub fn bulk_toggle_aliases(runcom_file: &str, db_file: &str) {
         return;
     }
 
-    for alias in selected_aliases {
-        // Remove the (enabled) or (disabled) from the alias name to get the actual alias name
-        crate::commands::aliases::edit::toggle_alias(
-            runcom_file,
-            db_file,
-            alias.split(' ').next().unwrap(),
-        );
-    }
+    let result = ctx.commit(|tx| {
+        for alias in &selected_aliases {
+            // Remove the (enabled) or (disabled) from the alias name to get the actual alias name
+            let mut alias = get_alias_by_name(tx, alias.split(' ').next().unwrap())?;
+            alias.enabled = !alias.enabled;
+            update_alias(tx, &alias.name.clone(), alias)?;
+        }
+        Ok(())
+    });
 
-    success!("Aliases toggled");
+    match result {
+        Ok(_) => success!("Aliases toggled"),
+        Err(err) => error!(format!("Could not toggle aliases: {}", err)),
+    }
 }
 
-pub fn add_alias(rc_file: &str, db_file: &str) {
+pub fn add_alias(ctx: &Context) {
     let command = match inquire::Text::new("Enter the command:").prompt() {
         Ok(cmd) => {
             if !cmd.is_empty() {
@@ -72,19 +75,11 @@ pub fn add_alias(rc_file: &str, db_file: &str) {
         .prompt()
         .unwrap_or("".to_string());
     // TODO: Allow adding to a group
-    crate::commands::aliases::add::add_alias(rc_file, db_file, &command, &description, 1);
+    crate::commands::aliases::add::add_alias(ctx, &command, &description, 1, None, false);
 }
 
-pub fn bulk_remove_aliases(runcom_file: &str, db_file: &str) {
-    let conn = match crate::file_management::database::setupdb(db_file) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to database");
-            return;
-        }
-    };
-
-    let aliases = crate::file_management::database::aliases::get_all_aliases(&conn);
+pub fn bulk_remove_aliases(ctx: &Context) {
+    let aliases = get_all_aliases(&ctx.conn);
 
     if aliases.is_empty() {
         error!("Could not find any aliases to remove");
@@ -107,23 +102,21 @@ pub fn bulk_remove_aliases(runcom_file: &str, db_file: &str) {
         return;
     }
 
-    for alias in selected_aliases {
-        crate::commands::aliases::remove::remove_alias(runcom_file, db_file, &alias, true);
-    }
+    let result = ctx.commit(|tx| {
+        for alias in &selected_aliases {
+            remove_alias(tx, alias)?;
+        }
+        Ok(())
+    });
 
-    success!("Aliases removed");
+    match result {
+        Ok(_) => success!("Aliases removed"),
+        Err(err) => error!(format!("Could not remove aliases: {}", err)),
+    }
 }
 
-pub fn rename_alias(runcom_file: &str, db_file: &str) {
-    let conn = match crate::file_management::database::setupdb(db_file) {
-        Ok(conn) => conn,
-        Err(_) => {
-            error!("issue connecting to database");
-            return;
-        }
-    };
-
-    let aliases = crate::file_management::database::aliases::get_all_aliases(&conn);
+pub fn rename_alias(ctx: &Context) {
+    let aliases = get_all_aliases(&ctx.conn);
 
     if aliases.is_empty() {
         error!("Could not find any aliases to rename");
@@ -139,5 +132,5 @@ pub fn rename_alias(runcom_file: &str, db_file: &str) {
     // TODO: Test this and add validation
     let new_name = inquire::Text::new("Enter the new name:").prompt().unwrap();
 
-    crate::commands::aliases::edit::rename(runcom_file, db_file, &selected_alias, &new_name);
+    crate::commands::aliases::edit::rename(ctx, &selected_alias, &new_name, false);
 }
@@ -1,9 +1,17 @@
-use crate::{error, helpers, success};
+use crate::{
+    commands::{
+        groups::toggle::{group_enabled, set_group_enabled},
+        Context,
+    },
+    error,
+    file_management::database::groups::{get_group_nameids, get_groups, remove_group},
+    helpers, success,
+};
 use console::style;
 
 macro_rules! get_group_vector {
     ($conn: expr) => {{
-        let mut groups = match crate::file_management::database::groups::get_group_nameids($conn) {
+        let mut groups = match get_group_nameids($conn) {
             Ok(g) => g,
             Err(_) => {
                 error!("Issue retrieving groups");
@@ -22,7 +30,7 @@ macro_rules! get_group_vector {
     }};
 }
 
-pub fn add_group(db_path: &str) {
+pub fn add_group(ctx: &Context) {
     // TODO: Validate group name
     // TODO: Create group name conventions
     let group_name = match inquire::Text::new("Enter new group name:").prompt() {
@@ -37,12 +45,11 @@ pub fn add_group(db_path: &str) {
         Err(_) => return,
     };
 
-    crate::commands::groups::add::add_group(db_path, &group_name)
+    crate::commands::groups::add::add_group(ctx, &group_name)
 }
 
-pub fn bulk_remove_group(rc_file: &str, db_path: &str) {
-    let conn = crate::file_management::database::db_conn(db_path);
-    let mut groups = match crate::file_management::database::groups::get_group_nameids(&conn) {
+pub fn bulk_remove_group(ctx: &Context) {
+    let mut groups = match get_group_nameids(&ctx.conn) {
         Ok(g) => g,
         Err(_) => {
             error!("Issue retrieving groups");
@@ -77,18 +84,21 @@ pub fn bulk_remove_group(rc_file: &str, db_path: &str) {
         return;
     }
 
-    for group in selected_groups {
-        crate::commands::groups::remove::remove_group(rc_file, db_path, group, true);
-    }
+    let result = ctx.commit(|tx| {
+        for group in &selected_groups {
+            remove_group(tx, group)?;
+        }
+        Ok(())
+    });
 
-    // TODO: Record if each group was removed then print success "All groups removed" else print the ones that failed
-    success!("Groups removed")
+    match result {
+        Ok(_) => success!("Groups removed"),
+        Err(err) => error!(format!("Could not remove groups: {}", err)),
+    }
 }
 
-pub fn rename_group(rc_file: &str, db_file: &str) {
-    let conn = crate::file_management::database::db_conn(db_file);
-
-    let groups = get_group_vector!(&conn);
+pub fn rename_group(ctx: &Context) {
+    let groups = get_group_vector!(&ctx.conn);
 
     let selected_group = inquire::Select::new(
         "Select a group to rename",
@@ -103,13 +113,11 @@ pub fn rename_group(rc_file: &str, db_file: &str) {
         return;
     }
 
-    crate::commands::groups::rename::rename_group(rc_file, db_file, selected_group, &new_name)
+    crate::commands::groups::rename::rename_group(ctx, selected_group, &new_name)
 }
 
-pub fn bulk_toggle_group(runcom_file: &str, db_file: &str) {
-    let conn = crate::file_management::database::db_conn(db_file);
-
-    let groups = crate::file_management::database::groups::get_groups(&conn);
+pub fn bulk_toggle_group(ctx: &Context) {
+    let groups = get_groups(&ctx.conn);
     if groups.is_empty() {
         error!("Could not find any groups");
         return;
@@ -146,55 +154,17 @@ pub fn bulk_toggle_group(runcom_file: &str, db_file: &str) {
         return;
     }
 
-    for group in selected_groups {
-        let group_name = group.split_whitespace().next().unwrap();
-        let enabled_flag = group.split_whitespace().last().unwrap();
-        if enabled_flag == "(enabled)" {
-            for alias in groups
-                .iter()
-                .find(|g| g.name == group_name)
-                .unwrap()
-                .aliases
-                .iter()
-            {
-                crate::commands::aliases::edit::toggle_alias(runcom_file, db_file, &alias.name)
-            }
-            for script in groups
-                .iter()
-                .find(|g| g.name == group_name)
-                .unwrap()
-                .scripts
-                .iter()
-            {
-                crate::commands::scripts::edit::toggle_script(runcom_file, db_file, &script.name)
-            }
-        } else {
-            for alias in groups
-                .iter()
-                .find(|g| g.name == group_name)
-                .unwrap()
-                .aliases
-                .iter()
-            {
-                if !alias.enabled {
-                    crate::commands::aliases::edit::toggle_alias(runcom_file, db_file, &alias.name)
-                }
-            }
-            for script in groups
-                .iter()
-                .find(|g| g.name == group_name)
-                .unwrap()
-                .scripts
-                .iter()
-            {
-                if !script.enabled {
-                    crate::commands::scripts::edit::toggle_script(
-                        runcom_file,
-                        db_file,
-                        &script.name,
-                    )
-                }
-            }
+    let result = ctx.commit(|tx| {
+        for selected in &selected_groups {
+            let group_name = selected.split_whitespace().next().unwrap();
+            let group = groups.iter().find(|g| g.name == group_name).unwrap();
+            set_group_enabled(tx, group, !group_enabled(group))?;
         }
+        Ok(())
+    });
+
+    match result {
+        Ok(_) => success!("Groups toggled"),
+        Err(err) => error!(format!("Could not toggle groups: {}", err)),
     }
 }
@@ -9,55 +9,41 @@ use std::io::{stdout, Write};
 use indexmap::IndexMap;
 use std::rc::Rc;
 
+use crate::commands::Context;
+
 #[derive(Clone)]
 enum MenuItem {
     Function(Rc<dyn Fn(&Context)>),
     SubMenu(IndexMap<&'static str, MenuItem>),
 }
 
-struct Context {
-    rc_file: String,
-    db_file: String,
-}
-
-pub fn start_manager(rc_file: &str, db_file: &str) {
+pub fn start_manager(ctx: Context) {
     let mut main_menu = IndexMap::new();
 
-    let context = Rc::new(Context {
-        rc_file: rc_file.to_string(),
-        db_file: db_file.to_string(),
-    });
+    let context = Rc::new(ctx);
 
     let mut alias_menu = IndexMap::new();
     alias_menu.insert(
         "List aliases",
         MenuItem::Function(Rc::new(|ctx| {
-            crate::commands::aliases::list::list_aliases(&ctx.db_file, false)
+            crate::commands::aliases::list::list_aliases(ctx, false)
         })),
     );
     alias_menu.insert(
         "Add alias",
-        MenuItem::Function(Rc::new(|ctx| {
-            alias_manager::add_alias(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(alias_manager::add_alias)),
     );
     alias_menu.insert(
         "Remove aliases",
-        MenuItem::Function(Rc::new(|ctx| {
-            alias_manager::bulk_remove_aliases(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(alias_manager::bulk_remove_aliases)),
     );
     alias_menu.insert(
         "Rename alias",
-        MenuItem::Function(Rc::new(|ctx| {
-            alias_manager::rename_alias(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(alias_manager::rename_alias)),
     );
     alias_menu.insert(
         "Toggle aliases",
-        MenuItem::Function(Rc::new(|ctx| {
-            alias_manager::bulk_toggle_aliases(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(alias_manager::bulk_toggle_aliases)),
     );
     // TODO: Add move alias
     //
@@ -66,57 +52,45 @@ pub fn start_manager(rc_file: &str, db_file: &str) {
     script_menu.insert(
         "List scripts",
         MenuItem::Function(Rc::new(|ctx| {
-            crate::commands::scripts::list::list_scripts(&ctx.db_file)
+            crate::commands::scripts::list::list_scripts(ctx)
         })),
     );
     // script_menu.insert("Add script", MenuItem::Function(|| {}));
     script_menu.insert(
         "Remove script",
-        MenuItem::Function(Rc::new(|ctx| {
-            script_manager::bulk_remove_scripts(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(script_manager::bulk_remove_scripts)),
     );
     script_menu.insert(
         "Rename script",
-        MenuItem::Function(Rc::new(|ctx| {
-            script_manager::rename_script(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(script_manager::rename_script)),
     );
     script_menu.insert(
         "Toggle script",
-        MenuItem::Function(Rc::new(|ctx| {
-            script_manager::bulk_toggle_scripts(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(script_manager::bulk_toggle_scripts)),
     );
 
     let mut group_menu = IndexMap::new();
     group_menu.insert(
         "List groups",
         MenuItem::Function(Rc::new(|ctx| {
-            crate::commands::groups::list::list_groups(&ctx.db_file)
+            crate::commands::groups::list::list_groups(ctx)
         })),
     );
     group_menu.insert(
         "Add group",
-        MenuItem::Function(Rc::new(|ctx| group_manager::add_group(&ctx.db_file))),
+        MenuItem::Function(Rc::new(group_manager::add_group)),
     );
     group_menu.insert(
         "Remove groups",
-        MenuItem::Function(Rc::new(|ctx| {
-            group_manager::bulk_remove_group(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(group_manager::bulk_remove_group)),
     );
     group_menu.insert(
         "Rename group",
-        MenuItem::Function(Rc::new(|ctx| {
-            group_manager::rename_group(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(group_manager::rename_group)),
     );
     group_menu.insert(
         "Toggle group",
-        MenuItem::Function(Rc::new(|ctx| {
-            group_manager::bulk_toggle_group(&ctx.rc_file, &ctx.db_file)
-        })),
+        MenuItem::Function(Rc::new(group_manager::bulk_toggle_group)),
     );
 
     main_menu.insert("Aliases", MenuItem::SubMenu(alias_menu));
@@ -1,10 +1,20 @@
 use console::style;
 
-use crate::{error, helpers};
+use crate::{
+    commands::{
+        scripts::{remove::remove_scripts, schedule::apply_schedules},
+        Context,
+    },
+    error,
+    file_management::{
+        database::scripts::{get_all_scripts, get_script_by_name, update_script},
+        Script,
+    },
+    helpers, success,
+};
 
-pub fn bulk_toggle_scripts(runcom_file: &str, db_file: &str) {
-    let conn = crate::file_management::database::db_conn(db_file);
-    let scripts = crate::file_management::database::scripts::get_all_scripts(&conn);
+pub fn bulk_toggle_scripts(ctx: &Context) {
+    let scripts = get_all_scripts(&ctx.conn);
     if scripts.is_empty() {
         error!("Could not find any scripts");
         return;
@@ -18,19 +28,37 @@ pub fn bulk_toggle_scripts(runcom_file: &str, db_file: &str) {
         return;
     }
 
-    for script in selected_scripts {
-        crate::commands::scripts::edit::toggle_script(runcom_file, db_file, &script);
+    let result = ctx.commit(|tx| {
+        let mut scheduled = false;
+        for script in &selected_scripts {
+            let mut script = get_script_by_name(tx, script)?;
+            script.enabled = !script.enabled;
+            update_script(tx, &script.name, &script)?;
+            scheduled |= script.schedule.is_some();
+        }
+        Ok(scheduled)
+    });
+
+    match result {
+        Ok(scheduled) => {
+            success!("Scripts toggled");
+            if scheduled {
+                if let Err(err) = apply_schedules(ctx) {
+                    error!(err);
+                }
+            }
+        }
+        Err(err) => error!(format!("Could not toggle scripts: {}", err)),
     }
 }
 
-// pub fn add_script(runcom_file: &str, db_file: &str) {
+// pub fn add_script(ctx: &Context) {
 //     // TODO: Implement this - auto complete script path
 //     todo!()
 // }
 
-pub fn bulk_remove_scripts(runcom_file: &str, db_file: &str) {
-    let conn = crate::file_management::database::db_conn(db_file);
-    let scripts = crate::file_management::database::scripts::get_all_scripts(&conn);
+pub fn bulk_remove_scripts(ctx: &Context) {
+    let scripts = get_all_scripts(&ctx.conn);
     if scripts.is_empty() {
         error!("Could not find any scripts");
         return;
@@ -49,14 +77,18 @@ pub fn bulk_remove_scripts(runcom_file: &str, db_file: &str) {
         return;
     }
 
-    for script in selected_scripts {
-        crate::commands::scripts::remove::remove_script(runcom_file, db_file, &script, true);
+    let selected_scripts: Vec<Script> = scripts
+        .into_iter()
+        .filter(|s| selected_scripts.contains(&s.name))
+        .collect();
+    match remove_scripts(ctx, &selected_scripts) {
+        Ok(_) => success!("Scripts removed"),
+        Err(err) => error!(err),
     }
 }
 
-pub fn rename_script(runcom_file: &str, db_file: &str) {
-    let conn = crate::file_management::database::db_conn(db_file);
-    let scripts = crate::file_management::database::scripts::get_all_scripts(&conn);
+pub fn rename_script(ctx: &Context) {
+    let scripts = get_all_scripts(&ctx.conn);
     if scripts.is_empty() {
         error!("Could not find any scripts");
         return;
@@ -75,5 +107,5 @@ pub fn rename_script(runcom_file: &str, db_file: &str) {
         return;
     }
 
-    crate::commands::scripts::edit::rename_script(runcom_file, db_file, selected_script, &new_name);
+    crate::commands::scripts::edit::rename_script(ctx, selected_script, &new_name, false);
 }